@@ -0,0 +1,12 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var t: texture_2d_array<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var s: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var<uniform> layer: u32;
+
+@fragment
+fn fragment(
+    v: VertexOutput,
+) -> @location(0) vec4<f32> {
+    return textureSample(t, s, v.uv, layer);
+}
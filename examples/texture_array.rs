@@ -0,0 +1,77 @@
+use bevy::{
+    image::ImageSamplerDescriptor,
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin},
+};
+use bevy_aseprite_ultra::prelude::*;
+
+/*
+ * Texture Array Example
+ * sample frames from a texture array by layer index,
+ * instead of looking up atlas rects.
+ */
+
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins.set(ImagePlugin {
+            default_sampler: ImageSamplerDescriptor::nearest(),
+        }))
+        .add_plugins(AsepriteUltraPlugin)
+        .add_plugins(Material2dPlugin::<ArrayMaterial>::default())
+        .add_systems(Startup, setup)
+        .add_systems(Update, render_animation::<MeshMaterial2d<ArrayMaterial>>)
+        .run();
+}
+
+#[derive(AsBindGroup, Debug, Clone, Asset, TypePath, Default)]
+pub struct ArrayMaterial {
+    #[texture(0, dimension = "2d_array")]
+    #[sampler(1)]
+    frames: Handle<Image>,
+    #[uniform(2)]
+    layer: u32,
+}
+
+impl Material2d for ArrayMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "texture_array.wgsl".into()
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl RenderAnimation for ArrayMaterial {
+    type Extra<'e> = ();
+    fn render_animation(&mut self, aseprite: &Aseprite, state: &AnimationState, _extra: &mut ()) {
+        let Some(texture_array) = aseprite.texture_array.as_ref() else {
+            return;
+        };
+        self.frames = texture_array.clone();
+        self.layer = aseprite.get_array_layer(usize::from(state.current_frame));
+    }
+}
+
+fn setup(
+    mut cmd: Commands,
+    server: Res<AssetServer>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ArrayMaterial>>,
+) {
+    cmd.spawn((Camera2d, Transform::default().with_scale(Vec3::splat(0.15))));
+    cmd.spawn((
+        AseAnimation {
+            aseprite: server.load_with_settings(
+                "player.aseprite",
+                |settings: &mut AsepriteLoaderSettings| {
+                    settings.texture_array = true;
+                },
+            ),
+            animation: Animation::tag("walk-down"),
+        },
+        Mesh2d(meshes.add(Mesh::from(Rectangle::from_size(Vec2::splat(100.0))))),
+        MeshMaterial2d(materials.add(ArrayMaterial::default())),
+    ));
+}
@@ -73,7 +73,7 @@ fn setup(mut cmd: Commands, server: Res<AssetServer>) {
                 ..default()
             },
             AseAnimation {
-                aseprite: server.load("player.aseprite").into(),
+                aseprite: server.load("player.aseprite"),
                 animation: Animation::default().with_tag("walk-right"),
             },
             ImageNode::default(),
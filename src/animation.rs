@@ -1,16 +1,19 @@
-use crate::loader::Aseprite;
-use anyhow::Context;
+use crate::{
+    error::{report, AseError, AsepriteError},
+    loader::Aseprite,
+    slice::ui_pivot_translation,
+};
 use aseprite_loader::binary::chunks::tags::AnimationDirection as RawDirection;
 use bevy::{
     app::{App, Plugin, PostUpdate, PreUpdate},
     ecs::component::Mutable,
     image::TextureAtlas,
     prelude::*,
-    sprite::Sprite,
+    sprite::{Anchor, Sprite},
     sprite_render::Material2d,
     ui::{widget::ImageNode, UiSystems},
 };
-use std::{collections::VecDeque, time::Duration};
+use std::{collections::VecDeque, ops::RangeInclusive, time::Duration};
 
 pub struct AsepriteAnimationPlugin;
 impl Plugin for AsepriteAnimationPlugin {
@@ -37,6 +40,15 @@ impl Plugin for AsepriteAnimationPlugin {
 ///
 /// # Examples
 /// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # #[derive(Component)]
+/// # struct MyMaterial {
+/// #     image: Handle<Image>,
+/// #     texture_min: UVec2,
+/// #     texture_max: UVec2,
+/// #     time: f32,
+/// # }
 /// impl RenderAnimation for MyMaterial {
 ///     type Extra<'e> = (Res<'e, Time>, Res<'e, Assets<TextureAtlasLayout>>);
 ///     fn render_animation(
@@ -144,15 +156,38 @@ pub struct AseAnimation {
     pub aseprite: Handle<Aseprite>,
 }
 
+/// an animated render target, with the components a frame anchor moves
+type AnimationTarget<'a, T> = (
+    &'a AseAnimation,
+    &'a mut T,
+    &'a AnimationState,
+    Option<&'a mut Anchor>,
+    Option<&'a mut UiTransform>,
+);
+
 pub fn render_animation<T: RenderAnimation + Component<Mutability = Mutable>>(
-    mut animations: Query<(&AseAnimation, &mut T, &AnimationState)>,
+    mut animations: Query<AnimationTarget<T>>,
     aseprites: Res<Assets<Aseprite>>,
     mut extra: <T as RenderAnimation>::Extra<'_>,
 ) {
-    for (animation, mut target, state) in &mut animations {
+    for (animation, mut target, state, maybe_anchor, maybe_ui_transform) in &mut animations {
         let Some(aseprite) = aseprites.get(&animation.aseprite) else {
             continue;
         };
+
+        // frames with an origin anchor the render target, otherwise the anchor is left alone
+        if let Some(frame_anchor) = aseprite.get_frame_anchor(usize::from(state.current_frame)) {
+            if let Some(mut anchor) = maybe_anchor {
+                anchor.set_if_neq(frame_anchor);
+            }
+            if let Some(mut ui_transform) = maybe_ui_transform {
+                let translation = ui_pivot_translation(&frame_anchor);
+                if ui_transform.translation != translation {
+                    ui_transform.translation = translation;
+                }
+            }
+        }
+
         target.render_animation(aseprite, state, &mut extra);
     }
 }
@@ -365,7 +400,19 @@ impl From<RawDirection> for AnimationDirection {
             RawDirection::Reverse => AnimationDirection::Reverse,
             RawDirection::PingPong => AnimationDirection::PingPong,
             RawDirection::PingPongReverse => AnimationDirection::PingPongReverse,
-            _ => panic!("Invalid AnimationDirection"),
+            // the loader warns about unknown directions
+            _ => AnimationDirection::Forward,
+        }
+    }
+}
+
+impl From<AnimationDirection> for RawDirection {
+    fn from(direction: AnimationDirection) -> RawDirection {
+        match direction {
+            AnimationDirection::Forward => RawDirection::Forward,
+            AnimationDirection::Reverse => RawDirection::Reverse,
+            AnimationDirection::PingPong => RawDirection::PingPong,
+            AnimationDirection::PingPongReverse => RawDirection::PingPongReverse,
         }
     }
 }
@@ -387,40 +434,46 @@ impl From<u16> for AnimationRepeat {
     }
 }
 
+/// an animation, with its playback state and diagnostics
+type AnimationPlayback<'a> = (
+    Entity,
+    &'a mut AseAnimation,
+    &'a mut AnimationState,
+    Has<ManualTick>,
+    Option<&'a AseError>,
+);
+
 pub fn update_aseprite_animation(
     mut cmd: Commands,
-    mut animations: Query<(
-        Entity,
-        &mut AseAnimation,
-        &mut AnimationState,
-        Has<ManualTick>,
-    )>,
+    mut animations: Query<AnimationPlayback>,
     aseprites: Res<Assets<Aseprite>>,
     time: Res<Time>,
-) -> Result<(), BevyError> {
-    for (entity, mut animation, mut state, is_manual) in animations.iter_mut() {
+) {
+    for (entity, mut animation, mut state, is_manual, error) in animations.iter_mut() {
         let Some(aseprite) = aseprites.get(&animation.aseprite) else {
             continue;
         };
 
-        let range = match animation.animation.tag.as_ref() {
-            Some(tag) => aseprite
-                .tags
-                .get(tag)
-                .map(|meta| meta.range.clone())
-                .context(format!(
-                    "Animation tag \"{tag}\" not found in aseprite file",
-                ))?,
-            None => 0..=(aseprite.frame_durations.len() as u16 - 1),
-        };
+        // missing tags play the whole file and mark the entity
+        let tag = animation.animation.tag.as_ref();
+        report(
+            &mut cmd,
+            entity,
+            error,
+            tag.filter(|tag| !aseprite.tags.contains_key(*tag))
+                .map(|tag| AsepriteError::MissingTag { tag: tag.clone() }),
+        );
+        let (range, direction) = playback(&animation.animation, aseprite);
+        let (first_frame, first_direction) = first_frame(&range, &direction);
 
         // has to check start and end! because hot reloading can cause
         // animations to be outside of the animation range
-        if !range.contains(&state.current_frame) {
+        if state.is_added() || !range.contains(&state.current_frame) {
             //Default code
             if !animation.animation.hold_relative_frame {
-                state.current_frame = *range.start();
-                state.relative_frame = 0;
+                state.current_frame = first_frame;
+                state.relative_frame = first_frame - range.start();
+                state.current_direction = first_direction;
                 animation.animation.relative_group = 0;
                 animation.animation.new_relative_group = 0;
 
@@ -428,12 +481,12 @@ pub fn update_aseprite_animation(
             } else {
                 if animation.animation.new_relative_group != animation.animation.relative_group {
                     animation.animation.relative_group = animation.animation.new_relative_group;
-                    state.current_frame = *range.start();
-                    state.relative_frame = 0;
+                    state.current_frame = first_frame;
+                    state.relative_frame = first_frame - range.start();
+                    state.current_direction = first_direction;
                     state.elapsed = std::time::Duration::ZERO;
                 } else {
-                    state.relative_frame =
-                        (state.relative_frame) % (*range.end() * range.start() - 1);
+                    state.relative_frame %= range.end() - range.start() + 1;
                     state.current_frame = *range.start() + state.relative_frame;
                 }
             }
@@ -447,23 +500,29 @@ pub fn update_aseprite_animation(
             continue;
         }
 
-        state.elapsed +=
-            std::time::Duration::from_secs_f32(time.delta_secs() * animation.animation.speed);
+        // exact at normal speed, so frames last exactly their duration
+        state.elapsed += match animation.animation.speed == 1.0 {
+            true => time.delta(),
+            false => time.delta().mul_f64(f64::from(animation.animation.speed)),
+        };
 
         let Some(frame_duration) = aseprite
             .frame_durations
             .get(usize::from(state.current_frame))
         else {
-            return Ok(());
+            continue;
         };
 
-        if state.elapsed > *frame_duration {
+        if state.elapsed >= *frame_duration {
             cmd.trigger(NextFrameEvent(entity));
-            state.elapsed =
-                Duration::from_secs_f32(state.elapsed.as_secs_f32() % frame_duration.as_secs_f32());
+            state.elapsed = match frame_duration.is_zero() {
+                true => Duration::ZERO,
+                false => Duration::from_nanos(
+                    (state.elapsed.as_nanos() % frame_duration.as_nanos()) as u64,
+                ),
+            };
         }
     }
-    Ok(())
 }
 
 #[derive(Event)]
@@ -483,156 +542,109 @@ fn next_frame(
         return;
     };
 
+    let (range, direction) = playback(&ase.animation, aseprite);
     let animation = &mut ase.animation;
 
-    let (range, direction) = match animation
-        .tag
-        .as_ref()
-        .map(|t| aseprite.tags.get(t))
-        .flatten()
-    {
-        Some(meta) => {
-            let dir = animation
-                .direction
-                .clone()
-                .unwrap_or(AnimationDirection::from(meta.direction));
-            (meta.range.clone(), dir)
-        }
-        None => {
-            let dir = animation
-                .direction
-                .clone()
-                .unwrap_or(AnimationDirection::Forward);
-            (0..=(aseprite.frame_durations.len() as u16 - 1), dir)
+    let next = match direction {
+        AnimationDirection::Forward => state
+            .current_frame
+            .checked_add(1)
+            .filter(|next| next <= range.end()),
+        AnimationDirection::Reverse => state
+            .current_frame
+            .checked_sub(1)
+            .filter(|next| next >= range.start()),
+        AnimationDirection::PingPong | AnimationDirection::PingPongReverse => {
+            match state.current_direction {
+                PlayDirection::Forward => state
+                    .current_frame
+                    .checked_add(1)
+                    .filter(|next| next <= range.end()),
+                PlayDirection::Backward => state
+                    .current_frame
+                    .checked_sub(1)
+                    .filter(|next| next >= range.start()),
+            }
         }
     };
 
-    match direction {
-        AnimationDirection::Forward => {
-            let next = state.current_frame + 1;
-
-            if next > *range.end() {
-                match animation.repeat {
-                    AnimationRepeat::Loop => {
-                        state.current_frame = *range.start();
-                        state.relative_frame = 0;
-                        events.write(AnimationEvents::LoopCycleFinished(trigger.0));
-                    }
-                    AnimationRepeat::Count(count) => {
-                        if count > 0 {
-                            state.current_frame = *range.start();
-                            state.relative_frame = 0;
-                            animation.repeat = AnimationRepeat::Count(count - 1);
-                        } else {
-                            if animation.queue.is_empty() {
-                                events.write(AnimationEvents::Finished(trigger.0));
-                            } else {
-                                animation.next();
-                            }
-                        }
-                    }
-                }
-            } else {
-                state.current_frame = next;
-                state.relative_frame += 1;
-            }
+    if let Some(next) = next {
+        state.current_frame = next;
+        state.relative_frame = next - range.start();
+        return;
+    }
+
+    // the end of a cycle, or a ping pong turn
+    match animation.repeat {
+        AnimationRepeat::Loop => {
+            events.write(AnimationEvents::LoopCycleFinished(trigger.0));
         }
-        AnimationDirection::Reverse => {
-            let next = state.current_frame.checked_sub(1).unwrap_or(*range.end());
-
-            if next == *range.end() {
-                match animation.repeat {
-                    AnimationRepeat::Loop => {
-                        state.current_frame = range.end() - 1;
-                        state.relative_frame = range.end() - range.start() - 1;
-                        events.write(AnimationEvents::LoopCycleFinished(trigger.0));
-                    }
-                    AnimationRepeat::Count(count) => {
-                        if count > 0 {
-                            state.current_frame = range.end() - 1;
-                            state.relative_frame = range.end() - range.start() - 1;
-                            animation.repeat = AnimationRepeat::Count(count - 1);
-                        } else {
-                            if animation.queue.is_empty() {
-                                events.write(AnimationEvents::Finished(trigger.0));
-                            } else {
-                                animation.next();
-                            }
-                        }
-                    }
-                }
+        AnimationRepeat::Count(count) if count > 0 => {
+            animation.repeat = AnimationRepeat::Count(count - 1);
+        }
+        AnimationRepeat::Count(_) => {
+            if animation.queue.is_empty() {
+                events.write(AnimationEvents::Finished(trigger.0));
             } else {
-                state.current_frame = next;
-                state
-                    .relative_frame
-                    .checked_sub(1)
-                    .unwrap_or(range.end() - range.start() - 1);
+                animation.next();
             }
+            return;
+        }
+    }
+
+    let (frame, play_direction) = match direction {
+        AnimationDirection::Forward | AnimationDirection::Reverse => {
+            first_frame(&range, &direction)
         }
         AnimationDirection::PingPong | AnimationDirection::PingPongReverse => {
-            let (next, relative_next) = match state.current_direction {
-                PlayDirection::Forward => (state.current_frame + 1, state.relative_frame + 1),
+            match state.current_direction {
+                PlayDirection::Forward => (
+                    range.end().saturating_sub(1).max(*range.start()),
+                    PlayDirection::Backward,
+                ),
                 PlayDirection::Backward => (
-                    state.relative_frame.checked_sub(1).unwrap_or(0),
-                    state.current_frame.checked_sub(1).unwrap_or(0),
+                    range.start().saturating_add(1).min(*range.end()),
+                    PlayDirection::Forward,
                 ),
-            };
-
-            let is_forward = match state.current_direction {
-                PlayDirection::Forward => true,
-                PlayDirection::Backward => false,
-            };
-
-            if next >= *range.end() && is_forward {
-                match animation.repeat {
-                    AnimationRepeat::Loop => {
-                        state.current_direction = PlayDirection::Backward;
-                        state.current_frame = range.end() - 2;
-                        state.relative_frame = range.end() - range.start() - 2;
-                        events.write(AnimationEvents::LoopCycleFinished(trigger.0));
-                    }
-                    AnimationRepeat::Count(count) => {
-                        if count > 0 {
-                            state.current_direction = PlayDirection::Backward;
-                            state.current_frame = range.end() - 2;
-                            state.relative_frame = range.end() - range.start() - 2;
-                            animation.repeat = AnimationRepeat::Count(count - 1);
-                        } else {
-                            if animation.queue.is_empty() {
-                                events.write(AnimationEvents::Finished(trigger.0));
-                            } else {
-                                animation.next();
-                            }
-                        }
-                    }
-                };
-            } else if next <= *range.start() && !is_forward {
-                match animation.repeat {
-                    AnimationRepeat::Loop => {
-                        state.current_direction = PlayDirection::Forward;
-                        state.current_frame = *range.start();
-                        state.relative_frame = 0;
-                        events.write(AnimationEvents::LoopCycleFinished(trigger.0));
-                    }
-                    AnimationRepeat::Count(count) => {
-                        if count > 0 {
-                            state.current_direction = PlayDirection::Forward;
-                            state.current_frame = *range.start();
-                            state.relative_frame = 0;
-                            animation.repeat = AnimationRepeat::Count(count - 1);
-                        } else {
-                            if animation.queue.is_empty() {
-                                events.write(AnimationEvents::Finished(trigger.0));
-                            } else {
-                                animation.next();
-                            }
-                        }
-                    }
-                };
-            } else {
-                state.current_frame = next;
-                state.relative_frame = relative_next;
             }
         }
     };
+    state.current_frame = frame;
+    state.relative_frame = frame - range.start();
+    state.current_direction = play_direction;
+}
+
+/// the frame range and direction an animation plays in
+fn playback(
+    animation: &Animation,
+    aseprite: &Aseprite,
+) -> (RangeInclusive<u16>, AnimationDirection) {
+    match animation.tag.as_ref().and_then(|tag| aseprite.tags.get(tag)) {
+        Some(meta) => (
+            meta.range.clone(),
+            animation
+                .direction
+                .clone()
+                .unwrap_or(AnimationDirection::from(meta.direction)),
+        ),
+        None => (
+            0..=((aseprite.frame_durations.len() as u16).saturating_sub(1)),
+            animation.direction.clone().unwrap_or_default(),
+        ),
+    }
+}
+
+/// the frame and play direction an animation starts with
+fn first_frame(
+    range: &RangeInclusive<u16>,
+    direction: &AnimationDirection,
+) -> (u16, PlayDirection) {
+    match direction {
+        AnimationDirection::Forward | AnimationDirection::PingPong => {
+            (*range.start(), PlayDirection::Forward)
+        }
+        AnimationDirection::Reverse | AnimationDirection::PingPongReverse => {
+            (*range.end(), PlayDirection::Backward)
+        }
+    }
 }
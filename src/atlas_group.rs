@@ -0,0 +1,348 @@
+use crate::{
+    animation::update_aseprite_animation,
+    error::AsepriteError,
+    loader::{Aseprite, MAX_ATLAS_SIZE},
+};
+use bevy::{
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension},
+};
+use std::ops::Range;
+
+pub struct AsepriteAtlasGroupPlugin;
+
+impl Plugin for AsepriteAtlasGroupPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AtlasGroups>();
+        app.add_systems(
+            PreUpdate,
+            (collect_group_members, pack_atlas_groups)
+                .chain()
+                .before(update_aseprite_animation),
+        );
+    }
+}
+
+/// All atlas groups, by name.
+///
+/// Aseprite files loaded with [`AsepriteLoaderSettings::atlas_group`](crate::prelude::AsepriteLoaderSettings::atlas_group)
+/// are repacked into their group atlas as soon as they are loaded.
+/// Until then, they render from their own atlas.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(server: Res<AssetServer>) {
+/// let enemy: Handle<Aseprite> = server.load_with_settings(
+///     "enemy.aseprite",
+///     |settings: &mut AsepriteLoaderSettings| {
+///         settings.atlas_group = Some("enemies".into());
+///     },
+/// );
+/// # }
+/// ```
+#[derive(Resource, Default)]
+pub struct AtlasGroups {
+    groups: HashMap<String, AtlasGroup>,
+}
+
+impl AtlasGroups {
+    pub fn get(&self, name: &str) -> Option<&AtlasGroup> {
+        self.groups.get(name)
+    }
+}
+
+/// One shared atlas image and layout
+pub struct AtlasGroup {
+    pub atlas_layout: Handle<TextureAtlasLayout>,
+    pub atlas_image: Handle<Image>,
+    members: Vec<GroupMember>,
+    dirty: bool,
+}
+
+impl AtlasGroup {
+    /// the aseprite files packed into this group
+    pub fn members(&self) -> impl Iterator<Item = AssetId<Aseprite>> + '_ {
+        self.members.iter().map(|member| member.aseprite)
+    }
+}
+
+/// the state of a file before it joined the group
+struct GroupMember {
+    aseprite: AssetId<Aseprite>,
+    atlas_layout: Handle<TextureAtlasLayout>,
+    atlas_image: Handle<Image>,
+    frame_indicies: Vec<usize>,
+    slice_ids: HashMap<String, usize>,
+    /// index, normal and emissive textures, laid out like `atlas_image`
+    aux_textures: [Option<Handle<Image>>; 3],
+}
+
+impl GroupMember {
+    fn images(&self) -> impl Iterator<Item = &Handle<Image>> {
+        std::iter::once(&self.atlas_image).chain(self.aux_textures.iter().flatten())
+    }
+}
+
+fn collect_group_members(
+    mut events: MessageReader<AssetEvent<Aseprite>>,
+    mut groups: ResMut<AtlasGroups>,
+    aseprites: Res<Assets<Aseprite>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for event in events.read() {
+        let id = match event {
+            AssetEvent::Added { id }
+            | AssetEvent::Modified { id }
+            | AssetEvent::LoadedWithDependencies { id } => *id,
+            AssetEvent::Removed { id } => {
+                for group in groups.groups.values_mut() {
+                    let count = group.members.len();
+                    group.members.retain(|member| member.aseprite != *id);
+                    group.dirty |= group.members.len() != count;
+                }
+                continue;
+            }
+            AssetEvent::Unused { .. } => continue,
+        };
+
+        let Some(aseprite) = aseprites.get(id) else {
+            continue;
+        };
+        let Some(name) = aseprite.atlas_group.as_ref() else {
+            continue;
+        };
+
+        let group = groups
+            .groups
+            .entry(name.clone())
+            .or_insert_with(|| AtlasGroup {
+                atlas_layout: layouts.add(TextureAtlasLayout::new_empty(UVec2::ONE)),
+                atlas_image: images.add(Image::default()),
+                members: Vec::new(),
+                dirty: false,
+            });
+
+        // already repacked, this is our own modification
+        if aseprite.atlas_image == group.atlas_image {
+            continue;
+        }
+
+        let member = GroupMember {
+            aseprite: id,
+            atlas_layout: aseprite.atlas_layout.clone(),
+            atlas_image: aseprite.atlas_image.clone(),
+            frame_indicies: aseprite.frame_indicies.clone(),
+            slice_ids: aseprite
+                .slices
+                .iter()
+                .map(|(name, slice)| (name.clone(), slice.atlas_id))
+                .collect(),
+            aux_textures: [
+                aseprite.index_texture.clone(),
+                aseprite.normal_texture.clone(),
+                aseprite.emissive_texture.clone(),
+            ],
+        };
+
+        match group
+            .members
+            .iter_mut()
+            .find(|member| member.aseprite == id)
+        {
+            Some(existing) => *existing = member,
+            None => group.members.push(member),
+        }
+        group.dirty = true;
+    }
+}
+
+fn pack_atlas_groups(
+    mut groups: ResMut<AtlasGroups>,
+    mut aseprites: ResMut<Assets<Aseprite>>,
+    mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (name, group) in groups.groups.iter_mut() {
+        if !group.dirty {
+            continue;
+        }
+
+        // wait until every member atlas is available
+        let ready = group.members.iter().all(|member| {
+            member.images().all(|image| images.contains(image))
+                && layouts.contains(&member.atlas_layout)
+        });
+        if !ready {
+            continue;
+        }
+        group.dirty = false;
+
+        if group.members.is_empty() {
+            continue;
+        }
+
+        let (layout, packed_image, ranges) = match pack_group(group, &layouts, &images) {
+            Ok(packed) => packed,
+            Err(err) => {
+                error!("failed to pack atlas group {name}: {err}");
+                continue;
+            }
+        };
+
+        // the index, normal and emissive textures follow the packed color atlas
+        let aux_textures: [Option<Handle<Image>>; 3] = std::array::from_fn(|aux| {
+            let image = pack_aux(group, aux, &layout, &ranges, &layouts, &images)?;
+            Some(images.add(image))
+        });
+
+        for (member, range) in group.members.iter().zip(ranges) {
+            let Some(aseprite) = aseprites.get_mut(member.aseprite) else {
+                continue;
+            };
+            let start = range.start;
+            aseprite.atlas_layout = group.atlas_layout.clone();
+            aseprite.atlas_image = group.atlas_image.clone();
+            aseprite.atlas_range = range;
+            aseprite.frame_indicies = member
+                .frame_indicies
+                .iter()
+                .map(|index| index + start)
+                .collect();
+            for (slice_name, slice) in aseprite.slices.iter_mut() {
+                if let Some(id) = member.slice_ids.get(slice_name) {
+                    slice.atlas_id = id + start;
+                }
+            }
+            let [index, normal, emissive] = std::array::from_fn(|aux| {
+                member.aux_textures[aux]
+                    .as_ref()
+                    .and(aux_textures[aux].clone())
+            });
+            aseprite.index_texture = index;
+            aseprite.normal_texture = normal;
+            aseprite.emissive_texture = emissive;
+        }
+
+        let _ = layouts.insert(&group.atlas_layout, layout);
+        let _ = images.insert(&group.atlas_image, packed_image);
+    }
+}
+
+/// packs the member atlases into one image, with the member layouts offset by their place in it,
+/// returns the rects of each member in the packed layout
+fn pack_group(
+    group: &AtlasGroup,
+    layouts: &Assets<TextureAtlasLayout>,
+    images: &Assets<Image>,
+) -> Result<(TextureAtlasLayout, Image, Vec<Range<usize>>), AsepriteError> {
+    let mut atlas_builder = TextureAtlasBuilder::default();
+    atlas_builder.max_size(UVec2::splat(MAX_ATLAS_SIZE));
+    for member in group.members.iter() {
+        let image = images
+            .get(&member.atlas_image)
+            .ok_or(AsepriteError::AtlasNotLoaded)?;
+        atlas_builder.add_texture(Some(member.atlas_image.id()), image);
+    }
+
+    let (packed_layout, source, mut packed_image) = atlas_builder
+        .build()
+        .map_err(AsepriteError::AtlasGroupError)?;
+    packed_image.sampler = images
+        .get(&group.members[0].atlas_image)
+        .map(|image| image.sampler.clone())
+        .unwrap_or_default();
+
+    let mut layout = TextureAtlasLayout::new_empty(packed_layout.size);
+    let mut ranges = Vec::with_capacity(group.members.len());
+    for member in group.members.iter() {
+        let (Some(packed), Some(member_layout)) = (
+            source.texture_rect(&packed_layout, member.atlas_image.id()),
+            layouts.get(&member.atlas_layout),
+        ) else {
+            return Err(AsepriteError::AtlasNotLoaded);
+        };
+        let start = layout.len();
+        for rect in member_layout.textures.iter() {
+            layout.add_texture(URect::from_corners(
+                rect.min + packed.min,
+                rect.max + packed.min,
+            ));
+        }
+        ranges.push(start..layout.len());
+    }
+    Ok((layout, packed_image, ranges))
+}
+
+/// copies the `aux` texture of every member to where its color atlas was packed,
+/// `None` if no member has one
+fn pack_aux(
+    group: &AtlasGroup,
+    aux: usize,
+    layout: &TextureAtlasLayout,
+    ranges: &[Range<usize>],
+    layouts: &Assets<TextureAtlasLayout>,
+    images: &Assets<Image>,
+) -> Option<Image> {
+    let first = group
+        .members
+        .iter()
+        .find_map(|member| images.get(member.aux_textures[aux].as_ref()?))?;
+    let format = first.texture_descriptor.format;
+    let pixel = format.block_copy_size(None)? as usize;
+    let stride = layout.size.x as usize * pixel;
+    let mut data = vec![0; stride * layout.size.y as usize];
+
+    for (member, range) in group.members.iter().zip(ranges) {
+        let (Some(image), Some(member_layout)) = (
+            member.aux_textures[aux]
+                .as_ref()
+                .and_then(|handle| images.get(handle)),
+            layouts.get(&member.atlas_layout),
+        ) else {
+            continue;
+        };
+        let (Some(source), Some(first_rect), Some(packed_rect)) = (
+            image.data.as_deref(),
+            member_layout.textures.first(),
+            layout.textures.get(range.start),
+        ) else {
+            continue;
+        };
+        if image.texture_descriptor.format != format {
+            warn!("atlas group member textures differ in format, skipping one");
+            continue;
+        }
+
+        // the member atlas is moved as a whole, its first rect tells where to
+        let offset = packed_rect.min - first_rect.min;
+        let size = image.size().min(layout.size.saturating_sub(offset));
+        let row = image.width() as usize * pixel;
+        let width = size.x as usize * pixel;
+        for y in 0..size.y as usize {
+            let Some(source) = source.get(y * row..y * row + width) else {
+                break;
+            };
+            let start = (offset.y as usize + y) * stride + offset.x as usize * pixel;
+            data[start..start + width].copy_from_slice(source);
+        }
+    }
+
+    Some(Image {
+        sampler: first.sampler.clone(),
+        ..Image::new(
+            Extent3d {
+                width: layout.size.x,
+                height: layout.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            format,
+            first.asset_usage,
+        )
+    })
+}
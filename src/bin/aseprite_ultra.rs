@@ -0,0 +1,317 @@
+//! Inspects and bakes aseprite files without launching the game.
+//!
+//! Files are loaded through the `AssetServer` with the same loader as in game,
+//! including the loader settings of a `.meta` file next to them.
+
+use aseprite_loader::loader::AsepriteFile;
+use bevy::{
+    asset::LoadState,
+    image::{ImagePlugin, TextureAtlasPlugin},
+    log::{Level, LogPlugin},
+    platform::collections::HashSet,
+    prelude::*,
+};
+use bevy_aseprite_ultra::prelude::*;
+use std::{
+    path::{Path, PathBuf},
+    process::ExitCode,
+    time::Duration,
+};
+
+const USAGE: &str = "usage: aseprite-ultra <file.aseprite> [options]
+
+Prints layers, frames, tags, slices and atlas stats of an aseprite file.
+Loader settings are read from a .meta file next to it.
+
+options:
+  --processed <out>        write the processed asset and its .meta file
+  --json-sheet             write the processed asset as sprite sheet json
+  --image-format <format>  atlas format of the processed asset: qoi, png or ktx2
+  --mipmaps                generate atlas mipmaps, ktx2 only
+  --atlas <out.png>        write the atlas image as png
+  --quiet                  print errors only
+  -h, --help               print this help";
+
+/// how long the asset server may take to load the file
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Default)]
+struct Args {
+    file: PathBuf,
+    processed: Option<PathBuf>,
+    atlas: Option<PathBuf>,
+    saver: AsepriteSaverSettings,
+    quiet: bool,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("error: {err}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("error: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `None` if help was requested
+fn parse_args() -> Result<Option<Args>, String> {
+    let mut args = Args::default();
+    let mut file = None;
+    let mut input = std::env::args().skip(1);
+
+    while let Some(arg) = input.next() {
+        let mut value = || input.next().ok_or_else(|| format!("{arg} expects a value"));
+        match arg.as_str() {
+            "--processed" => args.processed = Some(value()?.into()),
+            "--atlas" => args.atlas = Some(value()?.into()),
+            "--json-sheet" => args.saver.format = ProcessedFormat::JsonSheet,
+            "--image-format" => {
+                args.saver.image_format = match value()?.as_str() {
+                    "qoi" => AtlasImageFormat::Qoi,
+                    "png" => AtlasImageFormat::Png,
+                    "ktx2" => AtlasImageFormat::Ktx2,
+                    other => return Err(format!("unknown image format {other}")),
+                }
+            }
+            "--mipmaps" => args.saver.generate_mipmaps = true,
+            "--quiet" => args.quiet = true,
+            "-h" | "--help" => return Ok(None),
+            flag if flag.starts_with('-') => return Err(format!("unknown option {flag}")),
+            path => {
+                if file.replace(PathBuf::from(path)).is_some() {
+                    return Err("expected a single aseprite file".into());
+                }
+            }
+        }
+    }
+
+    args.file = file.ok_or("missing aseprite file")?;
+    Ok(Some(args))
+}
+
+fn run(args: &Args) -> Result<(), String> {
+    let path = std::path::absolute(&args.file).map_err(|err| err.to_string())?;
+    let bytes = std::fs::read(&path)
+        .map_err(|err| format!("failed to read {}: {err}", path.display()))?;
+    // layers are composited by the loader, only the raw file knows them
+    let raw = AsepriteFile::load(&bytes).map_err(|err| format!("{}: {err}", path.display()))?;
+
+    let (Some(dir), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(format!("{} is not a file", path.display()));
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        LogPlugin {
+            level: match args.quiet {
+                true => Level::ERROR,
+                false => Level::WARN,
+            },
+            ..default()
+        },
+        AssetPlugin {
+            file_path: dir.to_string_lossy().into(),
+            ..default()
+        },
+        ImagePlugin::default(),
+        TextureAtlasPlugin,
+        AsepriteUltraPlugin,
+    ));
+
+    let handle = app
+        .world()
+        .resource::<AssetServer>()
+        .load::<Aseprite>(name.to_string_lossy().into_owned());
+
+    let started = std::time::Instant::now();
+    loop {
+        app.update();
+        match app.world().resource::<AssetServer>().get_load_state(&handle) {
+            Some(LoadState::Loaded) => break,
+            Some(LoadState::Failed(err)) => return Err(describe(err.as_ref())),
+            _ if started.elapsed() > LOAD_TIMEOUT => {
+                return Err(format!("{} did not load in time", path.display()))
+            }
+            _ => std::thread::sleep(Duration::from_millis(1)),
+        }
+    }
+
+    let world = app.world();
+    let aseprite = world
+        .resource::<Assets<Aseprite>>()
+        .get(&handle)
+        .ok_or("aseprite is not loaded")?;
+    let atlas = world
+        .resource::<Assets<Image>>()
+        .get(&aseprite.atlas_image)
+        .ok_or("atlas image is not loaded")?;
+    let layout = world
+        .resource::<Assets<TextureAtlasLayout>>()
+        .get(&aseprite.atlas_layout)
+        .ok_or("atlas layout is not loaded")?;
+
+    if !args.quiet {
+        print_info(&raw, aseprite, atlas, layout);
+    }
+
+    if let Some(out) = args.processed.as_ref() {
+        let bytes = write_processed(aseprite, layout, atlas, &args.saver)
+            .map_err(|err| describe(&err))?;
+        let meta = PathBuf::from(format!("{}.meta", out.display()));
+        write(out, bytes)?;
+        write(&meta, processed_meta(atlas, &args.saver))?;
+    }
+
+    if let Some(out) = args.atlas.as_ref() {
+        atlas
+            .clone()
+            .try_into_dynamic()
+            .map_err(|err| format!("failed to convert atlas: {err}"))?
+            .save_with_format(out, image::ImageFormat::Png)
+            .map_err(|err| format!("failed to write {}: {err}", out.display()))?;
+    }
+
+    Ok(())
+}
+
+fn print_info(
+    raw: &AsepriteFile,
+    aseprite: &Aseprite,
+    atlas: &Image,
+    layout: &TextureAtlasLayout,
+) {
+    let (width, height) = raw.size();
+    let total = aseprite.frame_durations.iter().sum::<Duration>();
+    println!("canvas   {width}x{height}");
+    println!(
+        "frames   {}, {}ms total",
+        aseprite.frame_durations.len(),
+        total.as_millis()
+    );
+
+    // ---------------------------- atlas
+    let frame_rects = (0..aseprite.frame_durations.len())
+        .map(|frame| aseprite.get_atlas_index(frame))
+        .collect::<HashSet<_>>();
+    let used = frame_rects
+        .iter()
+        .filter_map(|index| layout.textures.get(*index))
+        .map(|rect| rect.width() * rect.height())
+        .sum::<u32>();
+    let size = atlas.size();
+    println!(
+        "atlas    {}x{}, {} rects, frames cover {:.1}%",
+        size.x,
+        size.y,
+        layout.len(),
+        used as f32 / (size.x * size.y).max(1) as f32 * 100.0
+    );
+    if !aseprite.palette.is_empty() {
+        println!("palette  {} colors", aseprite.palette.len());
+    }
+
+    println!("\nlayers");
+    for (index, layer) in raw.layers().iter().enumerate() {
+        println!(
+            "  {index:<3} {:<24} {:<8} opacity {:<3}  {:?}",
+            layer.name,
+            match layer.visible {
+                true => "visible",
+                false => "hidden",
+            },
+            layer.opacity,
+            layer.blend_mode,
+        );
+    }
+
+    println!("\nframes");
+    for (frame, duration) in aseprite.frame_durations.iter().enumerate() {
+        let index = aseprite.get_atlas_index(frame);
+        let rect = layout.textures.get(index).copied().unwrap_or_default();
+        println!(
+            "  {frame:<4} {:>6}ms  atlas {index} at {},{} {}x{}",
+            duration.as_millis(),
+            rect.min.x,
+            rect.min.y,
+            rect.width(),
+            rect.height()
+        );
+    }
+
+    println!("\ntags");
+    let mut tags = aseprite.tags.iter().collect::<Vec<_>>();
+    tags.sort_by(|(a_name, a), (b_name, b)| {
+        (a.range.start(), a_name).cmp(&(b.range.start(), b_name))
+    });
+    for (name, tag) in tags {
+        println!(
+            "  {name:<24} {:>3}..={:<3} {:?}, {}",
+            tag.range.start(),
+            tag.range.end(),
+            tag.direction,
+            match tag.repeat {
+                0 => "loop".to_string(),
+                count => format!("repeat {count}"),
+            }
+        );
+    }
+
+    println!("\nslices");
+    let mut slices = aseprite.slices.iter().collect::<Vec<_>>();
+    slices.sort_by_key(|(name, _)| *name);
+    for (name, slice) in slices {
+        let mut line = format!("  {name:<24} {}", rect(slice.rect));
+        if let Some(pivot) = slice.pivot {
+            line += &format!("  pivot {},{}", pivot.x, pivot.y);
+        }
+        if let Some(center) = slice.nine_patch {
+            line += &format!("  nine patch {},{} {}x{}", center.x, center.y, center.z, center.w);
+        }
+        if slice.keys.len() > 1 {
+            line += &format!("  {} keys", slice.keys.len());
+        }
+        if let Some(text) = slice.user_data.as_ref() {
+            line += &format!("  {text:?}");
+        }
+        println!("{line}");
+    }
+}
+
+fn rect(rect: Rect) -> String {
+    format!(
+        "{},{} {}x{}",
+        rect.min.x,
+        rect.min.y,
+        rect.width(),
+        rect.height()
+    )
+}
+
+fn write(path: &Path, bytes: Vec<u8>) -> Result<(), String> {
+    std::fs::write(path, bytes).map_err(|err| format!("failed to write {}: {err}", path.display()))
+}
+
+/// an error with all its sources
+fn describe(err: &dyn std::error::Error) -> String {
+    let mut message = err.to_string();
+    let mut source = err.source();
+    while let Some(err) = source {
+        message += &format!(": {err}");
+        source = err.source();
+    }
+    message
+}
@@ -0,0 +1,344 @@
+use crate::{
+    animation::{AnimationDirection, AnimationRepeat},
+    error::AsepriteError,
+    loader::{
+        clamp_tag_range, Aseprite, SliceKeyMeta, SliceMeta, SliceParts, TagMeta, MAX_ATLAS_SIZE,
+    },
+    writer::{encode, frame_parts},
+};
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use std::{ops::RangeInclusive, time::Duration};
+use uuid::Uuid;
+
+/// Builds an [`Aseprite`] in code, from frame images, durations, tags and slices.
+///
+/// [`AsepriteBuilder::build_atlas`] packs the frame images into an atlas,
+/// the result renders with [`AseAnimation`](crate::prelude::AseAnimation) and [`AseSlice`](crate::prelude::AseSlice).
+/// [`AsepriteBuilder::build`] skips the atlas, for logic and tests only relying on timing.
+///
+/// # Examples
+/// ```
+/// # use bevy::{
+/// #     asset::RenderAssetUsages,
+/// #     prelude::*,
+/// #     render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+/// # };
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # use std::time::Duration;
+/// # fn spark(frame: u8) -> Image {
+/// #     let size = Extent3d { width: 16, height: 16, depth_or_array_layers: 1 };
+/// #     let pixel = [255, 128 + frame * 32, 0, 255];
+/// #     Image::new_fill(size, TextureDimension::D2, &pixel, TextureFormat::Rgba8UnormSrgb, RenderAssetUsages::default())
+/// # }
+/// fn setup(
+///     mut cmd: Commands,
+///     mut aseprites: ResMut<Assets<Aseprite>>,
+///     mut images: ResMut<Assets<Image>>,
+///     mut layouts: ResMut<Assets<TextureAtlasLayout>>,
+/// ) {
+///     let aseprite = AsepriteBuilder::new()
+///         .frame_image(spark(0), Duration::from_millis(50))
+///         .frame_image(spark(1), Duration::from_millis(50))
+///         .frame_image(spark(2), Duration::from_millis(100))
+///         .tag("burst", 0..=2, AnimationDirection::PingPong, AnimationRepeat::Loop)
+///         .slice("core", Rect::new(4.0, 4.0, 12.0, 12.0), None)
+///         .build_atlas(&mut images, &mut layouts)
+///         .unwrap();
+///
+///     cmd.spawn((
+///         AseAnimation {
+///             aseprite: aseprites.add(aseprite),
+///             animation: Animation::tag("burst"),
+///         },
+///         Sprite::default(),
+///     ));
+/// }
+/// ```
+pub struct AsepriteBuilder {
+    frames: Vec<Option<Image>>,
+    frame_durations: Vec<Duration>,
+    tags: HashMap<String, TagMeta>,
+    slices: Vec<(String, SliceKeyMeta)>,
+    slice_user_data: HashMap<String, String>,
+    sampler: ImageSampler,
+}
+
+impl Default for AsepriteBuilder {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            frame_durations: Vec::new(),
+            tags: HashMap::new(),
+            slices: Vec::new(),
+            slice_user_data: HashMap::new(),
+            sampler: ImageSampler::nearest(),
+        }
+    }
+}
+
+impl AsepriteBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// adds a transparent frame
+    pub fn frame(mut self, duration: Duration) -> Self {
+        self.frames.push(None);
+        self.frame_durations.push(duration);
+        self
+    }
+
+    /// adds `count` transparent frames of the same duration
+    pub fn frames(mut self, count: usize, duration: Duration) -> Self {
+        self.frames.extend(std::iter::repeat_n(None, count));
+        self.frame_durations.extend(std::iter::repeat_n(duration, count));
+        self
+    }
+
+    /// adds a frame showing `image`
+    pub fn frame_image(mut self, image: Image, duration: Duration) -> Self {
+        self.frames.push(Some(image));
+        self.frame_durations.push(duration);
+        self
+    }
+
+    /// adds a tag over a range of frames
+    pub fn tag(
+        mut self,
+        name: impl Into<String>,
+        range: RangeInclusive<u16>,
+        direction: AnimationDirection,
+        repeat: AnimationRepeat,
+    ) -> Self {
+        self.tags.insert(
+            name.into(),
+            TagMeta {
+                direction: direction.into(),
+                range,
+                repeat: match repeat {
+                    AnimationRepeat::Loop => 0,
+                    AnimationRepeat::Count(count) => count.min(u16::MAX as u32) as u16,
+                },
+            },
+        );
+        self
+    }
+
+    /// adds a slice on the first frame, with an optional pivot relative to its rect
+    pub fn slice(self, name: impl Into<String>, rect: Rect, pivot: Option<Vec2>) -> Self {
+        self.slice_on(name, 0, rect, pivot)
+    }
+
+    /// adds a slice on the given frame, with an optional pivot relative to its rect
+    pub fn slice_on(
+        mut self,
+        name: impl Into<String>,
+        frame: usize,
+        rect: Rect,
+        pivot: Option<Vec2>,
+    ) -> Self {
+        self.slices.push((
+            name.into(),
+            SliceKeyMeta {
+                frame,
+                rect,
+                pivot,
+                nine_patch: None,
+            },
+        ));
+        self
+    }
+
+    /// sets the user data text of the slice `name`
+    pub fn slice_user_data(mut self, name: impl Into<String>, text: impl Into<String>) -> Self {
+        self.slice_user_data.insert(name.into(), text.into());
+        self
+    }
+
+    /// sets the sampler of the atlas image, nearest by default
+    pub fn sampler(mut self, sampler: ImageSampler) -> Self {
+        self.sampler = sampler;
+        self
+    }
+
+    /// Writes a .aseprite file with a single layer,
+    /// see [`write_aseprite`](crate::prelude::write_aseprite).
+    pub fn write(&self) -> Result<Vec<u8>, AsepriteError> {
+        let parts = frame_parts(
+            self.frames.iter().map(Option::as_ref),
+            self.frame_durations.clone(),
+            self.clamped_tags(),
+            self.grouped_slices(),
+            Vec::new(),
+        )?;
+        encode(&parts)
+    }
+
+    /// Builds the animation without an atlas, frame images are ignored.
+    pub fn build(self) -> Aseprite {
+        let tags = self.clamped_tags();
+        let slices = self
+            .grouped_slices()
+            .into_iter()
+            .map(|slice| (slice.name.clone(), slice_meta(slice, 0)))
+            .collect();
+        Aseprite {
+            frame_indicies: (0..self.frame_durations.len()).collect(),
+            frame_durations: self.frame_durations,
+            tags,
+            slices,
+            ..default()
+        }
+    }
+
+    /// Packs the frame images into an atlas and builds the animation.
+    /// Transparent frames are sized like the largest frame image.
+    pub fn build_atlas(
+        self,
+        images: &mut Assets<Image>,
+        layouts: &mut Assets<TextureAtlasLayout>,
+    ) -> Result<Aseprite, AsepriteError> {
+        if self.frames.is_empty() {
+            return Err(AsepriteError::NoFrames);
+        }
+
+        let size = self
+            .frames
+            .iter()
+            .flatten()
+            .map(Image::size)
+            .fold(UVec2::ONE, UVec2::max);
+        let blank = Image::new_fill(
+            Extent3d {
+                width: size.x,
+                height: size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            &[0, 0, 0, 0],
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::default(),
+        );
+
+        let mut atlas_builder = TextureAtlasBuilder::default();
+        atlas_builder.max_size(UVec2::splat(MAX_ATLAS_SIZE));
+        let frame_ids = (0..self.frames.len())
+            .map(|index| AssetId::Uuid {
+                uuid: Uuid::from_u128(index as u128),
+            })
+            .collect::<Vec<_>>();
+        for (id, image) in frame_ids.iter().zip(self.frames.iter()) {
+            atlas_builder.add_texture(Some(*id), image.as_ref().unwrap_or(&blank));
+        }
+
+        let (mut layout, source, mut atlas) = atlas_builder
+            .build()
+            .map_err(AsepriteError::BuilderAtlasError)?;
+        atlas.sampler = self.sampler.clone();
+
+        let frame_indicies = frame_ids
+            .iter()
+            .enumerate()
+            .map(|(frame, id)| {
+                source
+                    .texture_ids
+                    .get(id)
+                    .cloned()
+                    .ok_or(AsepriteError::MissingAtlasFrame(frame))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // slice rects are relative to their frame, the first key is packed like the loader does
+        let mut slices = HashMap::new();
+        for slice in self.grouped_slices() {
+            let key = &slice.keys[0];
+            let Some(frame_rect) = frame_indicies
+                .get(key.frame)
+                .and_then(|index| layout.textures.get(*index))
+            else {
+                warn!(
+                    "slice {} is on frame {}, which does not exist",
+                    slice.name, key.frame
+                );
+                continue;
+            };
+            let offset = frame_rect.min.as_vec2();
+            let atlas_id = layout.add_texture(URect::from_corners(
+                (key.rect.min + offset).as_uvec2(),
+                (key.rect.max + offset).as_uvec2(),
+            ));
+            slices.insert(slice.name.clone(), slice_meta(slice, atlas_id));
+        }
+
+        Ok(Aseprite {
+            atlas_range: 0..layout.len(),
+            atlas_layout: layouts.add(layout),
+            atlas_image: images.add(atlas),
+            frame_indicies,
+            tags: self.clamped_tags(),
+            frame_durations: self.frame_durations,
+            slices,
+            ..default()
+        })
+    }
+
+    /// the tags ordered and clamped to the frames, like the loader does
+    fn clamped_tags(&self) -> HashMap<String, TagMeta> {
+        let last_frame = self
+            .frame_durations
+            .len()
+            .saturating_sub(1)
+            .min(u16::MAX as usize) as u16;
+        self.tags
+            .iter()
+            .map(|(name, tag)| {
+                let range = clamp_tag_range(name, tag.range.clone(), last_frame);
+                (
+                    name.clone(),
+                    TagMeta {
+                        range,
+                        ..tag.clone()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// the slice keys grouped by name, in the order the slices were first added
+    fn grouped_slices(&self) -> Vec<SliceParts> {
+        let mut slices: Vec<SliceParts> = Vec::new();
+        for (name, key) in self.slices.iter() {
+            match slices.iter_mut().find(|slice| slice.name == *name) {
+                Some(slice) => slice.keys.push(key.clone()),
+                None => slices.push(SliceParts {
+                    name: name.clone(),
+                    keys: vec![key.clone()],
+                    user_data: self.slice_user_data.get(name).cloned(),
+                }),
+            }
+        }
+        for slice in slices.iter_mut() {
+            slice.keys.sort_by_key(|key| key.frame);
+        }
+        slices
+    }
+}
+
+/// a slice showing its first key, keys must not be empty
+fn slice_meta(slice: SliceParts, atlas_id: usize) -> SliceMeta {
+    let key = &slice.keys[0];
+    SliceMeta {
+        rect: key.rect,
+        atlas_id,
+        pivot: key.pivot,
+        nine_patch: key.nine_patch,
+        keys: slice.keys,
+        user_data: slice.user_data,
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    animation::{update_aseprite_animation, Animation, AnimationState, AseAnimation},
+    loader::Aseprite,
+    slice::AseSlice,
+};
+use bevy::{
+    ecs::entity::EntityHashSet,
+    prelude::*,
+    ui::{InteractionDisabled, UiSystems},
+};
+
+pub struct AsepriteButtonPlugin;
+
+impl Plugin for AsepriteButtonPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PreUpdate,
+            update_button
+                .after(UiSystems::Focus)
+                .before(update_aseprite_animation),
+        );
+        app.register_type::<AseButton>();
+        app.register_type::<AseButtonMode>();
+    }
+}
+
+/// A ui button, which swaps its look based on the [`Interaction`] state.
+///
+/// Resolves `{name}_normal`, `{name}_hover`, `{name}_pressed` and `{name}_disabled`
+/// from the aseprite file. Missing states fall back to `{name}_normal`.
+/// Add [`InteractionDisabled`] to show the disabled state.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(mut cmd: Commands, server: Res<AssetServer>) {
+/// cmd.spawn((
+///     Node {
+///         width: Val::Px(64.),
+///         height: Val::Px(32.),
+///         ..default()
+///     },
+///     AseButton {
+///         name: "button".into(),
+///         aseprite: server.load("menu.aseprite"),
+///         ..default()
+///     },
+/// ));
+/// # }
+/// ```
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[require(Button, ImageNode)]
+#[reflect]
+pub struct AseButton {
+    /// base name of the state slices or tags
+    pub name: String,
+    pub aseprite: Handle<Aseprite>,
+    pub mode: AseButtonMode,
+}
+
+/// How button states are represented in the aseprite file
+#[derive(Reflect, Default, Debug, Clone, PartialEq)]
+#[reflect]
+pub enum AseButtonMode {
+    /// each state is a slice, rendered with [`AseSlice`]
+    #[default]
+    Slice,
+    /// each state is a tag, looped with [`AseAnimation`]
+    Tag,
+}
+
+impl AseButton {
+    /// the slice or tag name for the current state
+    pub fn state_name(&self, interaction: &Interaction, disabled: bool) -> String {
+        let state = match (disabled, interaction) {
+            (true, _) => "disabled",
+            (false, Interaction::Pressed) => "pressed",
+            (false, Interaction::Hovered) => "hover",
+            (false, Interaction::None) => "normal",
+        };
+        format!("{}_{}", self.name, state)
+    }
+}
+
+/// a button, with the slice or animation its state is shown on
+type ButtonTarget<'a> = (
+    Entity,
+    Ref<'a, AseButton>,
+    Ref<'a, Interaction>,
+    Option<Ref<'a, InteractionDisabled>>,
+    Option<&'a mut AseSlice>,
+    Option<&'a mut AseAnimation>,
+);
+
+fn update_button(
+    mut cmd: Commands,
+    mut buttons: Query<ButtonTarget>,
+    mut enabled: RemovedComponents<InteractionDisabled>,
+    aseprites: Res<Assets<Aseprite>>,
+) {
+    let asset_change = aseprites.is_changed();
+    let enabled = enabled.read().collect::<EntityHashSet>();
+
+    for (entity, button, interaction, disabled, maybe_slice, maybe_animation) in &mut buttons {
+        let state_change = interaction.is_changed()
+            || disabled.as_ref().is_some_and(Ref::is_added)
+            || enabled.contains(&entity);
+        if !asset_change && !button.is_changed() && !state_change {
+            continue;
+        }
+        let Some(aseprite) = aseprites.get(&button.aseprite) else {
+            continue;
+        };
+
+        let mut name = button.state_name(&interaction, disabled.is_some());
+        let exists = match button.mode {
+            AseButtonMode::Slice => aseprite.slices.contains_key(&name),
+            AseButtonMode::Tag => aseprite.tags.contains_key(&name),
+        };
+        if !exists {
+            name = format!("{}_normal", button.name);
+        }
+
+        // a mode change removes the look of the previous mode
+        match button.mode {
+            AseButtonMode::Slice => match maybe_slice {
+                Some(mut slice) => {
+                    if slice.name != name || slice.aseprite != button.aseprite {
+                        slice.name = name;
+                        slice.aseprite = button.aseprite.clone();
+                    }
+                }
+                None => {
+                    cmd.entity(entity)
+                        .remove::<(AseAnimation, AnimationState)>()
+                        .insert(AseSlice {
+                            name,
+                            aseprite: button.aseprite.clone(),
+                        });
+                }
+            },
+            AseButtonMode::Tag => match maybe_animation {
+                Some(mut animation) => {
+                    if animation.animation.tag.as_ref() != Some(&name)
+                        || animation.aseprite != button.aseprite
+                    {
+                        animation.animation.play_loop(name);
+                        animation.aseprite = button.aseprite.clone();
+                    }
+                }
+                None => {
+                    cmd.entity(entity)
+                        .remove::<AseSlice>()
+                        .insert(AseAnimation {
+                            animation: Animation::tag(&name),
+                            aseprite: button.aseprite.clone(),
+                        });
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+#import bevy_aseprite_ultra::color_remap::{ColorRemap, remap_color}
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var t: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var<uniform> texture_min: vec2u;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var<uniform> texture_max: vec2u;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var<uniform> remap: ColorRemap;
+
+@fragment
+fn fragment(
+    v: VertexOutput,
+) -> @location(0) vec4<f32> {
+    return remap_color(t, texture_min, texture_max, v.uv, remap);
+}
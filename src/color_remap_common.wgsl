@@ -0,0 +1,33 @@
+#define_import_path bevy_aseprite_ultra::color_remap
+
+struct ColorRemap {
+    sources: array<vec4<f32>, 16>,
+    targets: array<vec4<f32>, 16>,
+    count: u32,
+};
+
+fn linear_to_srgb(c: vec3<f32>) -> vec3<f32> {
+    let low = c * 12.92;
+    let high = 1.055 * pow(c, vec3(1.0 / 2.4)) - 0.055;
+    return select(high, low, c <= vec3(0.0031308));
+}
+
+// samples the atlas rect at `uv` and replaces the first matching source color
+fn remap_color(
+    t: texture_2d<f32>,
+    texture_min: vec2u,
+    texture_max: vec2u,
+    uv: vec2<f32>,
+    remap: ColorRemap,
+) -> vec4<f32> {
+    let size = vec2f(texture_max - texture_min);
+    let pixel = min(texture_min + vec2u(uv * size), texture_max - vec2u(1u));
+    let col = textureLoad(t, pixel, 0);
+    let srgb = vec4(linear_to_srgb(col.rgb), col.a);
+    for (var i = 0u; i < remap.count; i++) {
+        if all(abs(srgb - remap.sources[i]) < vec4(0.5 / 255.0)) {
+            return remap.targets[i];
+        }
+    }
+    return col;
+}
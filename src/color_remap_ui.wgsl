@@ -0,0 +1,14 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+#import bevy_aseprite_ultra::color_remap::{ColorRemap, remap_color}
+
+@group(1) @binding(0) var t: texture_2d<f32>;
+@group(1) @binding(1) var<uniform> texture_min: vec2u;
+@group(1) @binding(2) var<uniform> texture_max: vec2u;
+@group(1) @binding(3) var<uniform> remap: ColorRemap;
+
+@fragment
+fn fragment(
+    v: UiVertexOutput,
+) -> @location(0) vec4<f32> {
+    return remap_color(t, texture_min, texture_max, v.uv, remap);
+}
@@ -0,0 +1,315 @@
+use crate::{error::AsepriteError, loader::AsepriteLoaderSettings};
+use aseprite_loader::{binary::blend_mode::BlendMode, loader::AsepriteFile};
+
+/// Composites every frame from the layers passing `include` into canvas sized rgba8 buffers.
+///
+/// Each cel is decoded once by aseprite-loader and blended here with a port of
+/// aseprite's `blend_funcs.cpp`, hiding layers or ignoring their opacity as the
+/// loader settings ask.
+pub(crate) fn composite_frames(
+    raw: &AsepriteFile,
+    settings: &AsepriteLoaderSettings,
+    include: impl Fn(&str) -> bool,
+) -> Result<Vec<Vec<u8>>, AsepriteError> {
+    let visible = raw
+        .layers
+        .iter()
+        .map(|layer| (layer.visible || settings.include_hidden_layers) && include(&layer.name))
+        .collect::<Vec<_>>();
+
+    (0..raw.frames.len())
+        .map(|frame| composite_frame(raw, frame, &visible, settings.ignore_layer_opacity))
+        .collect()
+}
+
+fn composite_frame(
+    raw: &AsepriteFile,
+    frame: usize,
+    visible: &[bool],
+    ignore_opacity: bool,
+) -> Result<Vec<u8>, AsepriteError> {
+    let (width, height) = raw.size();
+    let mut target = vec![0; usize::from(width) * usize::from(height) * 4];
+    let mut cel_buffer = Vec::new();
+
+    for cel in raw.frames[frame].cels.iter() {
+        // aseprite-loader panics on cels it can not decode, check them first
+        let Some(layer) = raw.layers.get(cel.layer_index) else {
+            return Err(AsepriteError::MissingLayer {
+                frame,
+                layer: cel.layer_index,
+            });
+        };
+        if !visible[cel.layer_index] {
+            continue;
+        }
+        if let BlendMode::Unknown(mode) = layer.blend_mode {
+            return Err(AsepriteError::UnsupportedBlendMode {
+                layer: layer.name.clone(),
+                mode,
+            });
+        }
+        cel_buffer.resize(usize::from(cel.size.0) * usize::from(cel.size.1) * 4, 0);
+        raw.load_image(cel.image_index, &mut cel_buffer)
+            .map_err(|source| AsepriteError::LoadingImageError {
+                frame,
+                layer: layer.name.clone(),
+                source,
+            })?;
+
+        let opacity = match ignore_opacity {
+            true => 255,
+            false => layer.opacity,
+        };
+        blend_cel(
+            &mut target,
+            (width, height),
+            &cel_buffer,
+            cel.origin,
+            cel.size,
+            |back, front| blend(layer.blend_mode, back, front, opacity),
+        );
+    }
+
+    Ok(target)
+}
+
+fn blend_cel(
+    target: &mut [u8],
+    (width, height): (u16, u16),
+    cel: &[u8],
+    origin: (i16, i16),
+    size: (u16, u16),
+    blend: impl Fn([u8; 4], [u8; 4]) -> [u8; 4],
+) {
+    for y in 0..size.1 {
+        let Some(target_y) = y.checked_add_signed(origin.1).filter(|y| *y < height) else {
+            continue;
+        };
+        for x in 0..size.0 {
+            let Some(target_x) = x.checked_add_signed(origin.0).filter(|x| *x < width) else {
+                continue;
+            };
+            let src = (usize::from(y) * usize::from(size.0) + usize::from(x)) * 4;
+            let dst = (usize::from(target_y) * usize::from(width) + usize::from(target_x)) * 4;
+            let back = [
+                target[dst],
+                target[dst + 1],
+                target[dst + 2],
+                target[dst + 3],
+            ];
+            let front = [cel[src], cel[src + 1], cel[src + 2], cel[src + 3]];
+            target[dst..dst + 4].copy_from_slice(&blend(back, front));
+        }
+    }
+}
+
+/// aseprite's blenders: the blend mode applies where the backdrop is opaque,
+/// elsewhere the source is composited `normal`
+fn blend(mode: BlendMode, back: [u8; 4], front: [u8; 4], opacity: u8) -> [u8; 4] {
+    if back[3] == 0 || matches!(mode, BlendMode::Normal) {
+        return normal(back, front, opacity);
+    }
+
+    let color = match mode {
+        BlendMode::Hue | BlendMode::Saturation | BlendMode::Color | BlendMode::Luminosity => {
+            let b = [back[0], back[1], back[2]].map(|v| f64::from(v) / 255.0);
+            let s = [front[0], front[1], front[2]].map(|v| f64::from(v) / 255.0);
+            let color = match mode {
+                BlendMode::Hue => set_lum(set_sat(s, sat(b)), lum(b)),
+                BlendMode::Saturation => set_lum(set_sat(b, sat(s)), lum(b)),
+                BlendMode::Color => set_lum(s, lum(b)),
+                _ => set_lum(b, lum(s)),
+            };
+            color.map(|v| (v * 255.0) as u8)
+        }
+        _ => [0, 1, 2].map(|i| blend_channel(mode, i32::from(back[i]), i32::from(front[i])) as u8),
+    };
+
+    let blended = normal(back, [color[0], color[1], color[2], front[3]], opacity);
+    let merged = merge(normal(back, front, opacity), blended, back[3]);
+    merge(
+        merged,
+        blended,
+        mul_un8(back[3], mul_un8(front[3], opacity)),
+    )
+}
+
+/// the separable blend modes, per channel
+fn blend_channel(mode: BlendMode, b: i32, s: i32) -> i32 {
+    match mode {
+        BlendMode::Multiply => multiply(b, s),
+        BlendMode::Screen => screen(b, s),
+        BlendMode::Overlay => hard_light(s, b),
+        BlendMode::Darken => b.min(s),
+        BlendMode::Lighten => b.max(s),
+        BlendMode::ColorDodge => match (b, 255 - s) {
+            (0, _) => 0,
+            (b, s) if b >= s => 255,
+            (b, s) => div8(b, s),
+        },
+        BlendMode::ColorBurn => match (255 - b, s) {
+            (0, _) => 255,
+            (b, s) if b >= s => 0,
+            (b, s) => 255 - div8(b, s),
+        },
+        BlendMode::HardLight => hard_light(b, s),
+        BlendMode::SoftLight => {
+            (soft_light(f64::from(b) / 255.0, f64::from(s) / 255.0) * 255.0 + 0.5) as i32
+        }
+        BlendMode::Difference => (b - s).abs(),
+        BlendMode::Exclusion => b + s - 2 * multiply(b, s),
+        BlendMode::Addition => (b + s).min(255),
+        BlendMode::Subtract => (b - s).max(0),
+        BlendMode::Divide => match (b, s) {
+            (0, _) => 0,
+            (b, s) if b >= s => 255,
+            (b, s) => div8(b, s),
+        },
+        _ => s,
+    }
+}
+
+fn multiply(b: i32, s: i32) -> i32 {
+    mul_i32(b, s as u8)
+}
+
+fn screen(b: i32, s: i32) -> i32 {
+    b + s - multiply(b, s)
+}
+
+fn hard_light(b: i32, s: i32) -> i32 {
+    match s < 128 {
+        true => multiply(b, s << 1),
+        false => screen(b, (s << 1) - 255),
+    }
+}
+
+/// `a * 255 / b` rounded, as used by aseprite
+fn div8(a: i32, b: i32) -> i32 {
+    (a * 255 + b / 2) / b
+}
+
+/// aseprite's `rgba_blender_normal`
+fn normal(back: [u8; 4], front: [u8; 4], opacity: u8) -> [u8; 4] {
+    if back[3] == 0 {
+        return [front[0], front[1], front[2], mul_un8(front[3], opacity)];
+    }
+    if front[3] == 0 {
+        return back;
+    }
+
+    let front_alpha = i32::from(mul_un8(front[3], opacity));
+    let back_alpha = i32::from(back[3]);
+    let alpha = front_alpha + back_alpha - i32::from(mul_un8(back[3], front_alpha as u8));
+    let channel = |b: u8, s: u8| {
+        let (b, s) = (i32::from(b), i32::from(s));
+        (b + (s - b) * front_alpha / alpha) as u8
+    };
+    [
+        channel(back[0], front[0]),
+        channel(back[1], front[1]),
+        channel(back[2], front[2]),
+        alpha as u8,
+    ]
+}
+
+/// aseprite's `rgba_blender_merge`
+fn merge(back: [u8; 4], front: [u8; 4], opacity: u8) -> [u8; 4] {
+    let mix = |b: u8, s: u8| (i32::from(b) + mul_i32(i32::from(s) - i32::from(b), opacity)) as u8;
+    let alpha = mix(back[3], front[3]);
+    if alpha == 0 {
+        return [0; 4];
+    }
+    match (back[3], front[3]) {
+        (0, _) => [front[0], front[1], front[2], alpha],
+        (_, 0) => [back[0], back[1], back[2], alpha],
+        _ => [
+            mix(back[0], front[0]),
+            mix(back[1], front[1]),
+            mix(back[2], front[2]),
+            alpha,
+        ],
+    }
+}
+
+fn soft_light(b: f64, s: f64) -> f64 {
+    let d = match b <= 0.25 {
+        true => ((16.0 * b - 12.0) * b + 4.0) * b,
+        false => b.sqrt(),
+    };
+    match s <= 0.5 {
+        true => b - (1.0 - 2.0 * s) * b * (1.0 - b),
+        false => b + (2.0 * s - 1.0) * (d - b),
+    }
+}
+
+fn lum(c: [f64; 3]) -> f64 {
+    0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+}
+
+fn sat(c: [f64; 3]) -> f64 {
+    c[0].max(c[1]).max(c[2]) - c[0].min(c[1]).min(c[2])
+}
+
+fn clip_color(c: [f64; 3]) -> [f64; 3] {
+    let l = lum(c);
+    let n = c[0].min(c[1]).min(c[2]);
+    let x = c[0].max(c[1]).max(c[2]);
+    let mut c = c;
+    if n < 0.0 {
+        c = c.map(|v| l + (v - l) * l / (l - n));
+    }
+    if x > 1.0 {
+        c = c.map(|v| l + (v - l) * (1.0 - l) / (x - l));
+    }
+    c
+}
+
+fn set_lum(c: [f64; 3], l: f64) -> [f64; 3] {
+    let d = l - lum(c);
+    clip_color(c.map(|v| v + d))
+}
+
+fn set_sat(mut c: [f64; 3], s: f64) -> [f64; 3] {
+    let [r, g, b] = c;
+    // picked like aseprite does, ties resolve differently from a sort
+    let min = match (r < g.min(b), g < b) {
+        (true, _) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+    };
+    let max = match (r > g.max(b), g > b) {
+        (true, _) => 0,
+        (false, true) => 1,
+        (false, false) => 2,
+    };
+    let mid = match (r > g, g > b) {
+        (true, true) => 1,
+        (true, false) if r > b => 2,
+        (true, false) => 0,
+        (false, true) if b > r => 2,
+        (false, true) => 0,
+        (false, false) => 1,
+    };
+
+    if c[max] > c[min] {
+        c[mid] = (c[mid] - c[min]) * s / (c[max] - c[min]);
+        c[max] = s;
+    } else {
+        c[mid] = 0.0;
+        c[max] = 0.0;
+    }
+    c[min] = 0.0;
+    c
+}
+
+fn mul_un8(a: u8, b: u8) -> u8 {
+    mul_i32(i32::from(a), b) as u8
+}
+
+/// `v * b / 255` rounded, as used by aseprite
+fn mul_i32(v: i32, b: u8) -> i32 {
+    let t = v * i32::from(b) + 0x80;
+    ((t >> 8) + t) >> 8
+}
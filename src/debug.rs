@@ -0,0 +1,427 @@
+use crate::{
+    animation::{AnimationState, AseAnimation},
+    loader::{Aseprite, SliceKeyMeta},
+    slice::AseSlice,
+};
+use bevy::{
+    ecs::entity::EntityHashMap, gizmos::GizmoPlugin, prelude::*, sprite::Anchor,
+    transform::TransformSystems,
+};
+
+/// Draws gizmos over [`AseAnimation`] and [`AseSlice`] sprites: bounds, slice rects,
+/// nine-patch centers, pivots and the current frame and tag as text.
+/// Only [`Sprite`] targets are drawn, ui [`ImageNode`]s are not.
+///
+/// Labels are separate top level entities following their animation,
+/// so the hierarchy of the animated entity is left alone.
+///
+/// Toggle it globally with the [`GizmoConfig`] of [`AseDebugGizmos`],
+/// or per entity with [`AseDebug`].
+pub struct AsepriteDebugPlugin;
+
+impl Plugin for AsepriteDebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_type::<AseDebug>();
+    }
+
+    fn finish(&self, app: &mut App) {
+        // gizmo groups need the gizmo assets, which headless apps do not have
+        if !app.is_plugin_added::<GizmoPlugin>() {
+            return;
+        }
+        app.init_gizmo_group::<AseDebugGizmos>();
+        app.add_systems(
+            PostUpdate,
+            (draw_animations, draw_slices, update_labels).after(TransformSystems::Propagate),
+        );
+    }
+}
+
+/// Gizmo group of the debug overlay.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// fn toggle_debug(mut store: ResMut<GizmoConfigStore>, keys: Res<ButtonInput<KeyCode>>) {
+///     let (config, debug) = store.config_mut::<AseDebugGizmos>();
+///     if keys.just_pressed(KeyCode::F3) {
+///         config.enabled = !config.enabled;
+///     }
+///     debug.labels = false;
+/// }
+/// ```
+#[derive(Reflect, GizmoConfigGroup)]
+pub struct AseDebugGizmos {
+    /// draws every entity, otherwise only those with [`AseDebug::Show`]
+    pub all_entities: bool,
+    pub bounds: bool,
+    pub slices: bool,
+    pub pivots: bool,
+    /// current frame and tag above animations
+    pub labels: bool,
+    pub bounds_color: Color,
+    pub slice_color: Color,
+    pub nine_patch_color: Color,
+    pub pivot_color: Color,
+}
+
+impl Default for AseDebugGizmos {
+    fn default() -> Self {
+        Self {
+            all_entities: true,
+            bounds: true,
+            slices: true,
+            pivots: true,
+            labels: true,
+            bounds_color: Color::srgb(0.2, 0.9, 0.3),
+            slice_color: Color::srgb(0.2, 0.6, 1.0),
+            nine_patch_color: Color::srgb(1.0, 0.8, 0.2),
+            pivot_color: Color::srgb(1.0, 0.2, 0.4),
+        }
+    }
+}
+
+/// Overrides [`AseDebugGizmos::all_entities`] for one entity.
+#[derive(Component, Reflect, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AseDebug {
+    Show,
+    Hide,
+}
+
+/// frame and tag text of an animation, the frame and tag it shows
+#[derive(Component)]
+struct AseDebugLabel {
+    frame: u16,
+    tag: Option<String>,
+}
+
+impl AseDebugLabel {
+    fn text(&self) -> String {
+        match self.tag.as_ref() {
+            Some(tag) => format!("{tag} {}", self.frame),
+            None => format!("frame {}", self.frame),
+        }
+    }
+}
+
+fn shown(debug: Option<&AseDebug>, config: &AseDebugGizmos) -> bool {
+    match debug {
+        Some(AseDebug::Show) => true,
+        Some(AseDebug::Hide) => false,
+        None => config.all_entities,
+    }
+}
+
+/// maps pixels of the shown image, y down, to world space
+struct Canvas<'a> {
+    transform: &'a GlobalTransform,
+    /// pixel size of the shown image
+    size: Vec2,
+    /// size of the sprite in local space
+    display: Vec2,
+    anchor: Vec2,
+    flip: BVec2,
+}
+
+impl<'a> Canvas<'a> {
+    fn new(
+        sprite: &Sprite,
+        transform: &'a GlobalTransform,
+        anchor: Option<&Anchor>,
+        layouts: &Assets<TextureAtlasLayout>,
+    ) -> Option<Self> {
+        let size = sprite
+            .texture_atlas
+            .as_ref()
+            .and_then(|atlas| atlas.texture_rect(layouts))?
+            .size()
+            .as_vec2();
+        Some(Self {
+            transform,
+            size,
+            display: sprite.custom_size.unwrap_or(size),
+            anchor: anchor.map(|anchor| anchor.0).unwrap_or_default(),
+            flip: BVec2::new(sprite.flip_x, sprite.flip_y),
+        })
+    }
+
+    fn point(&self, pixel: Vec2) -> Vec3 {
+        let mut normalized = Vec2::new(pixel.x / self.size.x - 0.5, 0.5 - pixel.y / self.size.y);
+        if self.flip.x {
+            normalized.x = -normalized.x;
+        }
+        if self.flip.y {
+            normalized.y = -normalized.y;
+        }
+        self.local(normalized - self.anchor)
+    }
+
+    fn local(&self, normalized: Vec2) -> Vec3 {
+        self.transform
+            .transform_point((normalized * self.display).extend(0.))
+    }
+
+    fn rect(&self, gizmos: &mut Gizmos<AseDebugGizmos>, rect: Rect, color: Color) {
+        gizmos.linestrip(
+            [
+                rect.min,
+                Vec2::new(rect.max.x, rect.min.y),
+                rect.max,
+                Vec2::new(rect.min.x, rect.max.y),
+                rect.min,
+            ]
+            .map(|pixel| self.point(pixel)),
+            color,
+        );
+    }
+
+    fn cross(&self, gizmos: &mut Gizmos<AseDebugGizmos>, pixel: Vec2, color: Color) {
+        for offset in [Vec2::X * 2., Vec2::Y * 2.] {
+            gizmos.line(
+                self.point(pixel - offset),
+                self.point(pixel + offset),
+                color,
+            );
+        }
+    }
+
+    /// the entity origin, where the anchor is placed
+    fn origin(&self, gizmos: &mut Gizmos<AseDebugGizmos>, color: Color) {
+        let pixel = 3. / self.size;
+        for offset in [Vec2::new(pixel.x, pixel.y), Vec2::new(pixel.x, -pixel.y)] {
+            gizmos.line(self.local(-offset), self.local(offset), color);
+        }
+    }
+
+    fn slice(
+        &self,
+        gizmos: &mut Gizmos<AseDebugGizmos>,
+        rect: Rect,
+        nine_patch: Option<Vec4>,
+        pivot: Option<Vec2>,
+        config: &AseDebugGizmos,
+    ) {
+        if config.slices {
+            self.rect(gizmos, rect, config.slice_color);
+            if let Some(center) = nine_patch {
+                let min = rect.min + center.xy();
+                self.rect(
+                    gizmos,
+                    Rect::from_corners(min, min + center.zw()),
+                    config.nine_patch_color,
+                );
+            }
+        }
+        if let (true, Some(pivot)) = (config.pivots, pivot) {
+            self.cross(gizmos, rect.min + pivot, config.pivot_color);
+        }
+    }
+}
+
+/// the key of a slice shown on `frame`
+fn active_key(keys: &[SliceKeyMeta], frame: usize) -> Option<&SliceKeyMeta> {
+    keys.iter().rev().find(|key| key.frame <= frame)
+}
+
+/// a drawn animation, with what places its canvas in the world
+type DebugAnimation<'a> = (
+    &'a AseAnimation,
+    &'a AnimationState,
+    &'a Sprite,
+    &'a GlobalTransform,
+    Option<&'a Anchor>,
+    Option<&'a AseDebug>,
+);
+
+fn draw_animations(
+    mut gizmos: Gizmos<AseDebugGizmos>,
+    animations: Query<DebugAnimation>,
+    aseprites: Res<Assets<Aseprite>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    let config = gizmos.config_ext;
+    for (animation, state, sprite, transform, anchor, debug) in animations.iter() {
+        if !shown(debug, config) {
+            continue;
+        }
+        let (Some(aseprite), Some(canvas)) = (
+            aseprites.get(&animation.aseprite),
+            Canvas::new(sprite, transform, anchor, &layouts),
+        ) else {
+            continue;
+        };
+
+        if config.bounds {
+            canvas.rect(
+                &mut gizmos,
+                Rect::from_corners(Vec2::ZERO, canvas.size),
+                config.bounds_color,
+            );
+        }
+        if config.pivots {
+            canvas.origin(&mut gizmos, config.pivot_color);
+        }
+
+        let frame = usize::from(state.current_frame);
+        for slice in aseprite.slices.values() {
+            if let Some(key) = active_key(&slice.keys, frame) {
+                canvas.slice(&mut gizmos, key.rect, key.nine_patch, key.pivot, config);
+            }
+        }
+    }
+}
+
+/// a drawn slice, with what places its canvas in the world
+type DebugSlice<'a> = (
+    &'a AseSlice,
+    &'a Sprite,
+    &'a GlobalTransform,
+    Option<&'a Anchor>,
+    Option<&'a AseDebug>,
+);
+
+fn draw_slices(
+    mut gizmos: Gizmos<AseDebugGizmos>,
+    slices: Query<DebugSlice>,
+    aseprites: Res<Assets<Aseprite>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    let config = gizmos.config_ext;
+    for (slice, sprite, transform, anchor, debug) in slices.iter() {
+        if !shown(debug, config) {
+            continue;
+        }
+        let (Some(meta), Some(canvas)) = (
+            aseprites
+                .get(&slice.aseprite)
+                .and_then(|aseprite| aseprite.slices.get(&slice.name)),
+            Canvas::new(sprite, transform, anchor, &layouts),
+        ) else {
+            continue;
+        };
+
+        if config.bounds {
+            canvas.rect(
+                &mut gizmos,
+                Rect::from_corners(Vec2::ZERO, canvas.size),
+                config.bounds_color,
+            );
+        }
+        if config.pivots {
+            canvas.origin(&mut gizmos, config.pivot_color);
+        }
+
+        // the slice is the whole image
+        canvas.slice(
+            &mut gizmos,
+            Rect::from_corners(Vec2::ZERO, canvas.size),
+            meta.nine_patch,
+            meta.pivot,
+            config,
+        );
+    }
+}
+
+/// a labeled animation, with what places the label above it
+type LabeledAnimation<'a> = (
+    Entity,
+    &'a AseAnimation,
+    &'a AnimationState,
+    &'a Sprite,
+    &'a GlobalTransform,
+    Option<&'a Anchor>,
+    Option<&'a AseDebug>,
+);
+
+/// a label, with the text and transforms it updates
+type Label<'a> = (
+    &'a mut AseDebugLabel,
+    &'a mut Text2d,
+    &'a mut Transform,
+    &'a mut GlobalTransform,
+);
+
+fn update_labels(
+    mut cmd: Commands,
+    store: Res<GizmoConfigStore>,
+    animations: Query<LabeledAnimation, Without<AseDebugLabel>>,
+    mut labels: Query<Label>,
+    mut spawned: Local<EntityHashMap<Entity>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+) {
+    let (gizmo_config, config) = store.config::<AseDebugGizmos>();
+    let enabled = gizmo_config.enabled && config.labels;
+
+    // labels of hidden or no longer animated entities
+    spawned.retain(|target, label| {
+        let keep = enabled
+            && labels.contains(*label)
+            && animations
+                .get(*target)
+                .is_ok_and(|(.., debug)| shown(debug, config));
+        if !keep {
+            cmd.entity(*label).try_despawn();
+        }
+        keep
+    });
+    if !enabled {
+        return;
+    }
+
+    for (entity, animation, state, sprite, transform, anchor, debug) in animations.iter() {
+        if !shown(debug, config) {
+            continue;
+        }
+
+        // above the sprite, labels are top level and placed in world space
+        let display = sprite.custom_size.unwrap_or_else(|| {
+            sprite
+                .texture_atlas
+                .as_ref()
+                .and_then(|atlas| atlas.texture_rect(&layouts))
+                .map(|rect| rect.size().as_vec2())
+                .unwrap_or_default()
+        });
+        let anchor = anchor.map(|anchor| anchor.0).unwrap_or_default();
+        let position = transform.transform_point(Vec3::new(
+            -anchor.x * display.x,
+            (0.5 - anchor.y) * display.y + 2.,
+            1.,
+        ));
+        let placed = Transform::from_translation(position);
+
+        let Some((mut label, mut text, mut label_transform, mut global)) = spawned
+            .get(&entity)
+            .and_then(|label| labels.get_mut(*label).ok())
+        else {
+            let label = AseDebugLabel {
+                frame: state.current_frame,
+                tag: animation.animation.tag.clone(),
+            };
+            let label = cmd
+                .spawn((
+                    Text2d::new(label.text()),
+                    label,
+                    TextFont::from_font_size(8.),
+                    TextColor(config.bounds_color),
+                    Anchor::BOTTOM_CENTER,
+                    placed,
+                ))
+                .id();
+            spawned.insert(entity, label);
+            continue;
+        };
+
+        if label.frame != state.current_frame || label.tag != animation.animation.tag {
+            label.frame = state.current_frame;
+            label.tag = animation.animation.tag.clone();
+            text.0 = label.text();
+        }
+        // propagation already ran this frame
+        if *label_transform != placed {
+            *label_transform = placed;
+            *global = GlobalTransform::from(placed);
+        }
+    }
+}
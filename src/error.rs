@@ -1,17 +1,61 @@
 use aseprite_loader::loader::{LoadImageError, LoadSpriteError};
-use bevy::image::TextureAtlasBuilderError;
+use bevy::{
+    asset::AssetPath, image::TextureAtlasBuilderError, prelude::*,
+    render::render_resource::TextureFormat,
+};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum AsepriteError {
-    #[error("failed to build atlas")]
-    TextureAtlasError(#[from] TextureAtlasBuilderError),
-    #[error("failed to read aseprite binary")]
-    LoadingError(#[from] LoadSpriteError),
-    #[error("failed to combine aseprite layers")]
-    LoadingImageError(#[from] LoadImageError),
-    #[error("failed to read byte stream")]
-    ReadError,
+    #[error("failed to build atlas of {path}")]
+    TextureAtlasError {
+        path: AssetPath<'static>,
+        #[source]
+        source: TextureAtlasBuilderError,
+    },
+    #[error("failed to build atlas of generated aseprite")]
+    BuilderAtlasError(#[source] TextureAtlasBuilderError),
+    #[error("failed to pack the member atlases: {0}")]
+    AtlasGroupError(#[source] TextureAtlasBuilderError),
+    #[error("failed to read aseprite binary {path}")]
+    LoadingError {
+        path: AssetPath<'static>,
+        #[source]
+        source: LoadSpriteError,
+    },
+    #[error("failed to combine layer \"{layer}\" of frame {frame}")]
+    LoadingImageError {
+        frame: usize,
+        layer: String,
+        #[source]
+        source: LoadImageError,
+    },
+    #[error("frame {frame} has a cel on layer {layer}, which does not exist")]
+    MissingLayer { frame: usize, layer: usize },
+    #[error("layer \"{layer}\" has the unsupported blend mode {mode}")]
+    UnsupportedBlendMode { layer: String, mode: u16 },
+    #[error("failed to read byte stream of {path}")]
+    ReadError {
+        path: AssetPath<'static>,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error("aseprite file has no frames")]
+    NoFrames,
+    #[error("canvas size {width}x{height} is empty or exceeds the atlas size of {max}")]
+    InvalidCanvasSize { width: u32, height: u32, max: u32 },
+    #[error("frame {0} is missing in the atlas")]
+    MissingAtlasFrame(usize),
+    #[error("atlas of the aseprite is not loaded")]
+    AtlasNotLoaded,
+    #[error("image format {0:?} can not be written")]
+    UnsupportedFormat(TextureFormat),
+    #[error("{0} frames exceed the aseprite limit of 65535")]
+    TooManyFrames(usize),
+    #[error("animation tag \"{tag}\" not found")]
+    MissingTag { tag: String },
+    #[error("slice \"{slice}\" not found")]
+    MissingSlice { slice: String },
     #[cfg(feature = "asset_processing")]
     #[error("failed to write to processed asset")]
     WriteError,
@@ -22,9 +66,47 @@ pub enum AsepriteError {
     #[error("failed to deserialize processed aseprite data {0}")]
     DeserializeError(#[from] rmp_serde::decode::Error),
     #[cfg(feature = "asset_processing")]
+    #[error("processed aseprite file is truncated")]
+    TruncatedProcessedFile,
+    #[cfg(feature = "asset_processing")]
+    #[error("not a processed aseprite file")]
+    UnsupportedProcessedFormat,
+    #[cfg(feature = "asset_processing")]
+    #[error("processed aseprite format version {found}, expected {expected}, delete the processed asset to reprocess it")]
+    ProcessedVersionMismatch { found: u16, expected: u16 },
+    #[cfg(feature = "asset_processing")]
     #[error("failed to write image data")]
     ImageError(#[from] image::ImageError),
-    #[cfg(feature = "asset_processing")]
     #[error("failed to read image data")]
     BevyTextureError(#[from] bevy::image::TextureError),
+    #[error("failed to parse sprite sheet json")]
+    SheetError(#[from] serde_json::Error),
+    #[error("failed to read sprite sheet image {image}")]
+    SheetImageError { image: String },
+}
+
+/// Marks an entity whose [`AseAnimation`](crate::prelude::AseAnimation)
+/// or [`AseSlice`](crate::prelude::AseSlice) can not render as requested.
+/// Removed as soon as it renders again.
+#[derive(Component, Debug)]
+pub struct AseError(pub AsepriteError);
+
+/// warns once and marks the entity with `error`, or removes the mark if `error` is none
+pub(crate) fn report(
+    cmd: &mut Commands,
+    entity: Entity,
+    current: Option<&AseError>,
+    error: Option<AsepriteError>,
+) {
+    match (current, error) {
+        (Some(current), Some(error)) if current.0.to_string() == error.to_string() => {}
+        (_, Some(error)) => {
+            warn!("{entity}: {error}");
+            cmd.entity(entity).try_insert(AseError(error));
+        }
+        (Some(_), None) => {
+            cmd.entity(entity).try_remove::<AseError>();
+        }
+        (None, None) => {}
+    }
 }
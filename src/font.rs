@@ -0,0 +1,320 @@
+use crate::loader::SliceMeta;
+use bevy::{platform::collections::HashMap, prelude::*, sprite::Anchor, ui::UiSystems};
+use serde::{Deserialize, Serialize};
+
+pub struct AsepriteFontPlugin;
+
+impl Plugin for AsepriteFontPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<BitmapFont>();
+        app.add_systems(PostUpdate, layout_text.before(UiSystems::Prepare));
+        app.register_type::<AseText>();
+    }
+}
+
+/// Where the glyphs of a [`BitmapFont`] are found in the aseprite file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum GlyphSource {
+    /// one slice per glyph, named `{prefix}{char}`.
+    /// The glyph advance is the slice width.
+    Slices { prefix: String },
+    /// a grid of equally sized cells on one frame, filled row by row with `characters`.
+    Grid {
+        characters: String,
+        cell_width: u32,
+        cell_height: u32,
+        frame: usize,
+    },
+}
+
+/// Builds a [`BitmapFont`] when loading an aseprite file.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(server: Res<AssetServer>) {
+/// let font: Handle<BitmapFont> = server.load_with_settings(
+///     "font.aseprite#font",
+///     |settings: &mut AsepriteLoaderSettings| {
+///         settings.font = Some(BitmapFontSettings::slices("glyph_"));
+///     },
+/// );
+/// # }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BitmapFontSettings {
+    pub source: GlyphSource,
+    /// extra space between glyphs in pixel
+    pub letter_spacing: f32,
+    /// distance between lines, defaults to the tallest glyph
+    pub line_height: Option<f32>,
+    /// advance of characters without a glyph, defaults to the widest glyph
+    pub space_advance: Option<f32>,
+}
+
+impl BitmapFontSettings {
+    /// glyphs from slices named `{prefix}{char}`
+    pub fn slices(prefix: impl Into<String>) -> Self {
+        Self {
+            source: GlyphSource::Slices {
+                prefix: prefix.into(),
+            },
+            letter_spacing: 0.0,
+            line_height: None,
+            space_advance: None,
+        }
+    }
+
+    /// glyphs from a grid on the first frame
+    pub fn grid(characters: impl Into<String>, cell_width: u32, cell_height: u32) -> Self {
+        Self {
+            source: GlyphSource::Grid {
+                characters: characters.into(),
+                cell_width,
+                cell_height,
+                frame: 0,
+            },
+            letter_spacing: 0.0,
+            line_height: None,
+            space_advance: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Glyph {
+    pub atlas_id: usize,
+    pub size: Vec2,
+    pub advance: f32,
+    /// offset applied to the advance, when followed by the given char
+    pub kerning: HashMap<char, f32>,
+}
+
+/// A pixel font made of aseprite slices or grid cells.
+///
+/// Kerning pairs are read from the slice user data, formatted as
+/// comma separated `{next char}:{offset}` pairs, e.g. `V:-1,A:-1`.
+#[derive(Asset, TypePath, Debug, Default)]
+pub struct BitmapFont {
+    pub glyphs: HashMap<char, Glyph>,
+    pub line_height: f32,
+    pub letter_spacing: f32,
+    pub space_advance: f32,
+    pub atlas_layout: Handle<TextureAtlasLayout>,
+    pub atlas_image: Handle<Image>,
+}
+
+impl BitmapFont {
+    /// builds the glyph map, grid cells are added to the layout
+    pub(crate) fn build(
+        settings: &BitmapFontSettings,
+        slices: &HashMap<String, SliceMeta>,
+        frame_indicies: &[usize],
+        layout: &mut TextureAtlasLayout,
+    ) -> Self {
+        let mut glyphs = HashMap::new();
+
+        match &settings.source {
+            GlyphSource::Slices { prefix } => {
+                for (name, slice) in slices.iter() {
+                    let Some(rest) = name.strip_prefix(prefix.as_str()) else {
+                        continue;
+                    };
+                    let mut chars = rest.chars();
+                    let (Some(char), None) = (chars.next(), chars.next()) else {
+                        continue;
+                    };
+                    let size = slice.rect.size();
+                    glyphs.insert(
+                        char,
+                        Glyph {
+                            atlas_id: slice.atlas_id,
+                            size,
+                            advance: size.x,
+                            kerning: slice
+                                .user_data
+                                .as_deref()
+                                .map(parse_kerning)
+                                .unwrap_or_default(),
+                        },
+                    );
+                }
+            }
+            GlyphSource::Grid {
+                characters,
+                cell_width,
+                cell_height,
+                frame,
+            } => {
+                let frame_rect = frame_indicies
+                    .get(*frame)
+                    .and_then(|index| layout.textures.get(*index))
+                    .cloned()
+                    .unwrap_or_default();
+                let cell = UVec2::new(*cell_width, *cell_height).max(UVec2::ONE);
+                let columns = (frame_rect.width() / cell.x).max(1);
+
+                for (index, char) in characters.chars().enumerate() {
+                    let index = index as u32;
+                    let min = frame_rect.min + UVec2::new(index % columns, index / columns) * cell;
+                    let atlas_id = layout.add_texture(URect::from_corners(min, min + cell));
+                    glyphs.insert(
+                        char,
+                        Glyph {
+                            atlas_id,
+                            size: cell.as_vec2(),
+                            advance: cell.x as f32,
+                            kerning: HashMap::new(),
+                        },
+                    );
+                }
+            }
+        }
+
+        let line_height = settings.line_height.unwrap_or_else(|| {
+            glyphs
+                .values()
+                .map(|glyph| glyph.size.y)
+                .fold(0.0, f32::max)
+        });
+        let space_advance = settings.space_advance.unwrap_or_else(|| {
+            glyphs
+                .values()
+                .map(|glyph| glyph.advance)
+                .fold(0.0, f32::max)
+        });
+
+        Self {
+            glyphs,
+            line_height,
+            letter_spacing: settings.letter_spacing,
+            space_advance,
+            ..default()
+        }
+    }
+
+    /// the distance to the next glyph, including kerning
+    pub fn advance(&self, char: char, next: Option<char>) -> f32 {
+        let Some(glyph) = self.glyphs.get(&char) else {
+            return self.space_advance + self.letter_spacing;
+        };
+        let kerning = next
+            .and_then(|next| glyph.kerning.get(&next))
+            .cloned()
+            .unwrap_or_default();
+        glyph.advance + self.letter_spacing + kerning
+    }
+}
+
+fn parse_kerning(user_data: &str) -> HashMap<char, f32> {
+    user_data
+        .split(',')
+        .filter_map(|pair| {
+            let (char, offset) = pair.trim().rsplit_once(':')?;
+            let mut chars = char.chars();
+            let (Some(char), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            Some((char, offset.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+/// Renders text with a [`BitmapFont`].
+///
+/// Glyphs are spawned as children, as [`Sprite`]s or, if the entity is a
+/// ui [`Node`], as absolute positioned [`ImageNode`]s.
+/// The text starts at the top left.
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect]
+pub struct AseText {
+    pub text: String,
+    pub font: Handle<BitmapFont>,
+    pub color: Color,
+}
+
+/// A glyph spawned by [`AseText`]
+#[derive(Component)]
+pub struct AseGlyph;
+
+/// a text, with the glyphs spawned for it
+type TextLayout<'a> = (Entity, Ref<'a, AseText>, Has<Node>, Option<&'a Children>);
+
+fn layout_text(
+    mut cmd: Commands,
+    texts: Query<TextLayout>,
+    glyphs: Query<(), With<AseGlyph>>,
+    fonts: Res<Assets<BitmapFont>>,
+) {
+    let asset_change = fonts.is_changed();
+
+    for (entity, text, is_ui, maybe_children) in &texts {
+        if !asset_change && !text.is_changed() {
+            continue;
+        }
+        let Some(font) = fonts.get(&text.font) else {
+            continue;
+        };
+
+        if let Some(children) = maybe_children {
+            for &child in children {
+                if glyphs.contains(child) {
+                    cmd.entity(child).despawn();
+                }
+            }
+        }
+
+        let mut pen = Vec2::ZERO;
+        let mut chars = text.text.chars().peekable();
+        while let Some(char) = chars.next() {
+            if char == '\n' {
+                pen = Vec2::new(0.0, pen.y + font.line_height);
+                continue;
+            }
+
+            let advance = font.advance(char, chars.peek().cloned());
+
+            if let Some(glyph) = font.glyphs.get(&char) {
+                let atlas = TextureAtlas {
+                    layout: font.atlas_layout.clone(),
+                    index: glyph.atlas_id,
+                };
+
+                let child = match is_ui {
+                    true => cmd
+                        .spawn((
+                            AseGlyph,
+                            Node {
+                                position_type: PositionType::Absolute,
+                                left: Val::Px(pen.x),
+                                top: Val::Px(pen.y),
+                                width: Val::Px(glyph.size.x),
+                                height: Val::Px(glyph.size.y),
+                                ..default()
+                            },
+                            ImageNode {
+                                color: text.color,
+                                ..ImageNode::from_atlas_image(font.atlas_image.clone(), atlas)
+                            },
+                        ))
+                        .id(),
+                    false => cmd
+                        .spawn((
+                            AseGlyph,
+                            Sprite {
+                                color: text.color,
+                                ..Sprite::from_atlas_image(font.atlas_image.clone(), atlas)
+                            },
+                            Anchor::TOP_LEFT,
+                            Transform::from_translation(Vec3::new(pen.x, -pen.y, 0.0)),
+                        ))
+                        .id(),
+                };
+                cmd.entity(entity).add_child(child);
+            }
+
+            pen.x += advance;
+        }
+    }
+}
@@ -5,19 +5,56 @@
 use bevy::prelude::*;
 
 pub(crate) mod animation;
+pub(crate) mod atlas_group;
+pub(crate) mod builder;
+pub(crate) mod button;
+pub(crate) mod composite;
+#[cfg(feature = "debug")]
+pub(crate) mod debug;
 pub(crate) mod error;
+pub(crate) mod font;
+pub(crate) mod lighting;
 pub(crate) mod loader;
+pub(crate) mod palette;
 #[cfg(feature = "asset_processing")]
 pub(crate) mod processor;
+pub(crate) mod remap;
+pub(crate) mod sheet;
 pub(crate) mod slice;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "window")]
+pub(crate) mod window;
+pub(crate) mod writer;
 
 pub mod prelude {
     pub use crate::animation::{
         render_animation, Animation, AnimationDirection, AnimationEvents, AnimationRepeat,
         AnimationState, AseAnimation, ManualTick, NextFrameEvent, PlayDirection, RenderAnimation,
     };
-    pub use crate::loader::{Aseprite, AsepriteLoaderSettings, SliceMeta};
+    pub use crate::atlas_group::{AtlasGroup, AtlasGroups};
+    pub use crate::builder::AsepriteBuilder;
+    pub use crate::button::{AseButton, AseButtonMode};
+    #[cfg(feature = "debug")]
+    pub use crate::debug::{AseDebug, AseDebugGizmos};
+    pub use crate::error::{AseError, AsepriteError};
+    pub use crate::font::{AseGlyph, AseText, BitmapFont, BitmapFontSettings, Glyph, GlyphSource};
+    #[cfg(feature = "asset_processing")]
+    pub use crate::processor::{
+        processed_meta, write_processed, AsepriteSaverSettings, AtlasImageFormat, ProcessedFormat,
+    };
+    pub use crate::lighting::{LitMaterial, LitMaterialPlugin};
+    pub use crate::loader::{Aseprite, AsepriteLoaderSettings, SliceMeta, SubImages};
+    pub use crate::palette::{
+        palette_image, PaletteSwapMaterial, PaletteSwapPlugin, PLACEHOLDER_PALETTE,
+    };
+    pub use crate::remap::{
+        ColorRemap, ColorRemapMaterial, ColorRemapPlugin, ColorRemapUniform, MAX_REMAP_COLORS,
+    };
     pub use crate::slice::{render_slice, AseSlice, RenderSlice};
+    #[cfg(feature = "window")]
+    pub use crate::window::AseWindowIcon;
+    pub use crate::writer::write_aseprite;
     pub use crate::AsepriteUltraPlugin;
 }
 
@@ -26,12 +63,12 @@ pub mod prelude {
 /// Quick guide:
 ///
 /// add the plugin to your game
-/// ```rust
+/// ```rust,no_run
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
 /// fn main() {
 ///     App::new()
-///         .add_plugins(DefaultPlugins.set(ImagePlugin {
-///             default_sampler: bevy::render::texture::ImageSamplerDescriptor::nearest(),
-///         }))
+///         .add_plugins(DefaultPlugins.set(ImagePlugin::default_nearest()))
 ///         .add_plugins(AsepriteUltraPlugin)
 ///         .add_systems(Startup, setup)
 ///         .run();
@@ -40,38 +77,57 @@ pub mod prelude {
 /// // spawn sprites, animations and ui
 /// fn setup(mut cmd: Commands, server: Res<AssetServer>) {
 ///     // ui animation
-///     cmd.spawn(AseUiAnimation {
-///         aseprite: server.load("player.aseprite").into(),
-///         animation: Animation::default().with_tag("walk-right"),
-///     });
+///     cmd.spawn((
+///         AseAnimation {
+///             aseprite: server.load("player.aseprite"),
+///             animation: Animation::tag("walk-right"),
+///         },
+///         ImageNode::default(),
+///     ));
 ///
 ///     // sprite animation
-///     cmd.spawn(AseSpriteAnimation {
-///         aseprite: server.load("player.aseprite").into(),
-///         animation: Animation::default().with_tag("walk-right"),
-///     });
+///     cmd.spawn((
+///         AseAnimation {
+///             aseprite: server.load("player.aseprite"),
+///             animation: Animation::tag("walk-right"),
+///         },
+///         Sprite::default(),
+///     ));
 ///
 ///     // static sprite
-///     cmd.spawn(AseSpriteSlice {
-///         name: "ghost_red".into(),
-///         aseprite: server.load("ghost_slices.aseprite"),
-///     });
+///     cmd.spawn((
+///         AseSlice {
+///             name: "ghost_red".into(),
+///             aseprite: server.load("ghost_slices.aseprite"),
+///         },
+///         Sprite::default(),
+///     ));
 ///
 ///     // static ui
-///     cmd.spawn(AseUiSlice {
-///         name: "ghost_red".into(),
-///         aseprite: server.load("ghost_slices.aseprite"),
-///     });
+///     cmd.spawn((
+///         AseSlice {
+///             name: "ghost_red".into(),
+///             aseprite: server.load("ghost_slices.aseprite"),
+///         },
+///         ImageNode::default(),
+///     ));
 /// }
-///
 /// ```
 pub struct AsepriteUltraPlugin;
 impl Plugin for AsepriteUltraPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(loader::AsepriteLoaderPlugin);
+        app.add_plugins(sheet::AsepriteSheetLoaderPlugin);
         app.add_plugins(slice::AsepriteSlicePlugin);
         app.add_plugins(animation::AsepriteAnimationPlugin);
+        app.add_plugins(atlas_group::AsepriteAtlasGroupPlugin);
+        app.add_plugins(button::AsepriteButtonPlugin);
+        app.add_plugins(font::AsepriteFontPlugin);
         #[cfg(feature = "asset_processing")]
         app.add_plugins(processor::AsepriteProcessorPlugin);
+        #[cfg(feature = "window")]
+        app.add_plugins(window::AsepriteWindowPlugin);
+        #[cfg(feature = "debug")]
+        app.add_plugins(debug::AsepriteDebugPlugin);
     }
 }
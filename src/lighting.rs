@@ -0,0 +1,164 @@
+use crate::{
+    animation::{render_animation, AnimationState, RenderAnimation},
+    loader::{Aseprite, SliceMeta},
+    slice::{render_slice, RenderSlice},
+};
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin},
+};
+
+/// Renders [`LitMaterial`] animations and slices on 2d meshes.
+///
+/// Not part of [`AsepriteUltraPlugin`](crate::prelude::AsepriteUltraPlugin),
+/// since it requires the render plugins.
+pub struct LitMaterialPlugin;
+
+impl Plugin for LitMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "lit.wgsl");
+        app.add_plugins(Material2dPlugin::<LitMaterial>::default());
+        app.add_systems(
+            PostUpdate,
+            (
+                render_animation::<MeshMaterial2d<LitMaterial>>,
+                render_slice::<MeshMaterial2d<LitMaterial>>,
+            ),
+        );
+    }
+}
+
+/// Lights pixel art with the normal and emissive atlases of a file.
+///
+/// Load the file with [`AsepriteLoaderSettings::normal_layer_suffix`](crate::prelude::AsepriteLoaderSettings::normal_layer_suffix)
+/// and [`AsepriteLoaderSettings::emissive_layer_suffix`](crate::prelude::AsepriteLoaderSettings::emissive_layer_suffix).
+/// A single directional light is applied, missing atlases are skipped.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(
+/// #     mut cmd: Commands,
+/// #     server: Res<AssetServer>,
+/// #     mut meshes: ResMut<Assets<Mesh>>,
+/// #     mut materials: ResMut<Assets<LitMaterial>>,
+/// # ) {
+/// cmd.spawn((
+///     AseAnimation {
+///         aseprite: server.load_with_settings(
+///             "player.aseprite",
+///             |settings: &mut AsepriteLoaderSettings| {
+///                 settings.normal_layer_suffix = Some("_normal".into());
+///                 settings.emissive_layer_suffix = Some("_emissive".into());
+///             },
+///         ),
+///         animation: Animation::tag("walk-right"),
+///     },
+///     Mesh2d(meshes.add(Rectangle::from_size(Vec2::splat(32.0)))),
+///     MeshMaterial2d(materials.add(LitMaterial::default())),
+/// ));
+/// # }
+/// ```
+#[derive(AsBindGroup, Asset, TypePath, Debug, Clone)]
+pub struct LitMaterial {
+    #[texture(0)]
+    #[sampler(1)]
+    pub color: Handle<Image>,
+    #[texture(2)]
+    pub normal: Option<Handle<Image>>,
+    #[texture(3)]
+    pub emissive: Option<Handle<Image>>,
+    #[uniform(4)]
+    pub texture_min: UVec2,
+    #[uniform(5)]
+    pub texture_max: UVec2,
+    /// direction the light travels, in view space with z towards the camera
+    #[uniform(6)]
+    pub light_direction: Vec3,
+    #[uniform(7)]
+    pub light_color: LinearRgba,
+    #[uniform(8)]
+    pub ambient: LinearRgba,
+    /// bit 0: normal atlas bound, bit 1: emissive atlas bound
+    #[uniform(9)]
+    pub flags: u32,
+}
+
+impl Default for LitMaterial {
+    fn default() -> Self {
+        Self {
+            color: Handle::default(),
+            normal: None,
+            emissive: None,
+            texture_min: UVec2::ZERO,
+            texture_max: UVec2::ZERO,
+            light_direction: Vec3::new(-1.0, -1.0, -1.0).normalize(),
+            light_color: LinearRgba::WHITE,
+            ambient: LinearRgba::gray(0.3),
+            flags: 0,
+        }
+    }
+}
+
+impl LitMaterial {
+    fn bind_atlases(&mut self, aseprite: &Aseprite) {
+        self.color = aseprite.atlas_image.clone();
+        self.normal = aseprite.normal_texture.clone();
+        self.emissive = aseprite.emissive_texture.clone();
+        self.flags = self.normal.is_some() as u32 | ((self.emissive.is_some() as u32) << 1);
+    }
+}
+
+impl Material2d for LitMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_aseprite_ultra/lit.wgsl".into()
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl RenderAnimation for LitMaterial {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_animation(
+        &mut self,
+        aseprite: &Aseprite,
+        state: &AnimationState,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let Some(atlas_layout) = extra.get(&aseprite.atlas_layout) else {
+            return;
+        };
+        let index = aseprite.get_atlas_index(usize::from(state.current_frame));
+        let Some(rect) = atlas_layout.textures.get(index) else {
+            return;
+        };
+        self.bind_atlases(aseprite);
+        self.texture_min = rect.min;
+        self.texture_max = rect.max;
+    }
+}
+
+impl RenderSlice for LitMaterial {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_slice(
+        &mut self,
+        aseprite: &Aseprite,
+        slice_meta: &SliceMeta,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let Some(rect) = extra
+            .get(&aseprite.atlas_layout)
+            .and_then(|layout| layout.textures.get(slice_meta.atlas_id))
+        else {
+            return;
+        };
+        self.bind_atlases(aseprite);
+        self.texture_min = rect.min;
+        self.texture_max = rect.max;
+    }
+}
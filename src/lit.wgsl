@@ -0,0 +1,37 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var color_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var s: sampler;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var normal_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var emissive_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(4) var<uniform> texture_min: vec2u;
+@group(#{MATERIAL_BIND_GROUP}) @binding(5) var<uniform> texture_max: vec2u;
+@group(#{MATERIAL_BIND_GROUP}) @binding(6) var<uniform> light_direction: vec3<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(7) var<uniform> light_color: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(8) var<uniform> ambient: vec4<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(9) var<uniform> flags: u32;
+
+@fragment
+fn fragment(
+    v: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let size = vec2f(textureDimensions(color_texture));
+    let uv = mix(vec2f(texture_min), vec2f(texture_max), v.uv) / size;
+    let color = textureSample(color_texture, s, uv);
+
+    var light = vec3(1.0);
+    if (flags & 1u) != 0u {
+        let sampled = textureSample(normal_texture, s, uv);
+        // pixels without a normal face the camera
+        let normal = select(vec3(0.0, 0.0, 1.0), normalize(sampled.xyz * 2.0 - 1.0), sampled.a > 0.0);
+        let diffuse = max(dot(normal, -normalize(light_direction)), 0.0);
+        light = ambient.rgb + light_color.rgb * diffuse;
+    }
+
+    var lit = vec4(color.rgb * light, color.a);
+    if (flags & 2u) != 0u {
+        let emissive = textureSample(emissive_texture, s, uv);
+        lit = vec4(lit.rgb + emissive.rgb * emissive.a, max(lit.a, emissive.a));
+    }
+    return lit;
+}
@@ -1,16 +1,33 @@
-use crate::error::AsepriteError;
-use aseprite_loader::{binary::chunks::tags::AnimationDirection, loader::AsepriteFile};
+use crate::{
+    composite::composite_frames,
+    error::AsepriteError,
+    font::{BitmapFont, BitmapFontSettings},
+    palette::{index_image, palette_image},
+};
+use aseprite_loader::{
+    binary::{
+        chunk::Chunk,
+        chunks::tags::AnimationDirection,
+        raw_file::{parse_raw_file, RawFile},
+    },
+    loader::{AsepriteFile, LoadSpriteError},
+};
 use bevy::{
-    asset::{io::Reader, AssetLoader, RenderAssetUsages},
+    asset::{io::Reader, AssetLoader, LoadContext, RenderAssetUsages},
     image::ImageSampler,
     platform::collections::HashMap,
     prelude::*,
-    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    render::render_resource::{
+        Extent3d, TextureDimension, TextureFormat, TextureViewDescriptor, TextureViewDimension,
+    },
     sprite::Anchor,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+/// the largest atlas the loader packs, in pixels per side
+pub(crate) const MAX_ATLAS_SIZE: u32 = 4096;
+
 pub struct AsepriteLoaderPlugin;
 impl Plugin for AsepriteLoaderPlugin {
     fn build(&self, app: &mut App) {
@@ -25,14 +42,51 @@ impl Plugin for AsepriteLoaderPlugin {
 #[derive(Asset, Default, TypePath, Debug)]
 #[cfg_attr(feature = "asset_processing", derive(Serialize, Deserialize))]
 pub struct Aseprite {
+    #[cfg_attr(
+        feature = "asset_processing",
+        serde(serialize_with = "serialize_sorted")
+    )]
     pub slices: HashMap<String, SliceMeta>,
+    #[cfg_attr(
+        feature = "asset_processing",
+        serde(serialize_with = "serialize_sorted")
+    )]
     pub tags: HashMap<String, TagMeta>,
     pub frame_durations: Vec<std::time::Duration>,
     #[cfg_attr(feature = "asset_processing", serde(skip))]
     pub atlas_layout: Handle<TextureAtlasLayout>,
     #[cfg_attr(feature = "asset_processing", serde(skip))]
     pub atlas_image: Handle<Image>,
+    /// all frames as layers of a 2d array texture,
+    /// see [`AsepriteLoaderSettings::texture_array`]
+    #[cfg_attr(feature = "asset_processing", serde(skip))]
+    pub texture_array: Option<Handle<Image>>,
+    /// the color palette of the file, empty for sprite sheets
+    pub palette: Vec<Color>,
+    /// the palette as 1d texture, labeled `palette`
+    #[cfg_attr(feature = "asset_processing", serde(skip))]
+    pub palette_texture: Option<Handle<Image>>,
+    /// palette indices of the atlas, see [`AsepriteLoaderSettings::index_texture`]
+    #[cfg_attr(feature = "asset_processing", serde(skip))]
+    pub index_texture: Option<Handle<Image>>,
+    /// linear normal map atlas with the layout of `atlas_layout`,
+    /// see [`AsepriteLoaderSettings::normal_layer_suffix`]
+    #[cfg_attr(feature = "asset_processing", serde(skip))]
+    pub normal_texture: Option<Handle<Image>>,
+    /// emissive atlas with the layout of `atlas_layout`,
+    /// see [`AsepriteLoaderSettings::emissive_layer_suffix`]
+    #[cfg_attr(feature = "asset_processing", serde(skip))]
+    pub emissive_texture: Option<Handle<Image>>,
     pub(crate) frame_indicies: Vec<usize>,
+    /// the atlas group this file is packed into, see [`AsepriteLoaderSettings::atlas_group`]
+    pub atlas_group: Option<String>,
+    /// the indices of this file in `atlas_layout`.
+    /// Starts at zero, unless the file is part of an atlas group.
+    pub atlas_range: std::ops::Range<usize>,
+    pub(crate) sub_images: SubImages,
+    /// normalized anchor per frame, derived from the origin, the origin slice or a pivot slice.
+    /// `None` for frames covered by neither.
+    pub(crate) frame_anchors: Vec<Option<Vec2>>,
 }
 
 impl Aseprite {
@@ -42,9 +96,74 @@ impl Aseprite {
         }
         self.frame_indicies[frame]
     }
+
+    /// the frames of tag `name`, sharing the atlas and textures of this file.
+    /// Frame numbers, the tag range and slice keys are relative to the tag.
+    /// The texture array and atlas group are not shared, their layers and rects are per file.
+    pub(crate) fn tag_view(&self, name: &str) -> Option<Aseprite> {
+        fn frames<T: Clone>(frames: &[T], range: std::ops::RangeInclusive<usize>) -> Vec<T> {
+            frames.get(range).map(<[T]>::to_vec).unwrap_or_default()
+        }
+
+        let tag = self.tags.get(name)?;
+        let start = usize::from(*tag.range.start());
+        let end = usize::from(*tag.range.end()).max(start);
+        let last = end - start;
+
+        Some(Aseprite {
+            slices: self
+                .slices
+                .iter()
+                .map(|(name, slice)| {
+                    let mut slice = slice.clone();
+                    for key in slice.keys.iter_mut() {
+                        key.frame = key.frame.saturating_sub(start).min(last);
+                    }
+                    (name.clone(), slice)
+                })
+                .collect(),
+            tags: [(
+                name.to_string(),
+                TagMeta {
+                    range: 0..=last as u16,
+                    ..tag.clone()
+                },
+            )]
+            .into(),
+            frame_durations: frames(&self.frame_durations, start..=end),
+            atlas_layout: self.atlas_layout.clone(),
+            atlas_image: self.atlas_image.clone(),
+            texture_array: None,
+            palette: self.palette.clone(),
+            palette_texture: self.palette_texture.clone(),
+            index_texture: self.index_texture.clone(),
+            normal_texture: self.normal_texture.clone(),
+            emissive_texture: self.emissive_texture.clone(),
+            frame_indicies: frames(&self.frame_indicies, start..=end),
+            atlas_group: None,
+            atlas_range: self.atlas_range.clone(),
+            sub_images: self.sub_images,
+            frame_anchors: frames(&self.frame_anchors, start..=end),
+        })
+    }
+
+    /// the layer of a frame in [`Aseprite::texture_array`]
+    pub fn get_array_layer(&self, frame: usize) -> u32 {
+        frame.min(self.frame_durations.len().saturating_sub(1)) as u32
+    }
+
+    /// returns the anchor of a frame, if the file has an origin or the frame carries a pivot slice.
+    /// see [`AsepriteLoaderSettings::origin`] and [`AsepriteLoaderSettings::origin_slice`]
+    pub fn get_frame_anchor(&self, frame: usize) -> Option<Anchor> {
+        let anchor = match self.frame_anchors.get(frame) {
+            Some(anchor) => *anchor,
+            None => self.frame_anchors.last().copied().flatten(),
+        };
+        anchor.map(Anchor)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "asset_processing", derive(Serialize, Deserialize))]
 pub struct TagMeta {
     #[cfg_attr(feature = "asset_processing", serde(with = "AnimationDirectionDef"))]
@@ -64,7 +183,18 @@ enum AnimationDirectionDef {
     Unknown(u8),
 }
 
-#[derive(Debug)]
+/// serializes maps in key order, so processed output is reproducible
+#[cfg(feature = "asset_processing")]
+fn serialize_sorted<S: serde::Serializer, V: Serialize>(
+    map: &HashMap<String, V>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    map.iter()
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .serialize(serializer)
+}
+
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "asset_processing", derive(Serialize, Deserialize))]
 pub struct SliceKeyMeta {
     pub frame: usize,
@@ -73,45 +203,214 @@ pub struct SliceKeyMeta {
     pub nine_patch: Option<Vec4>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 #[cfg_attr(feature = "asset_processing", derive(Serialize, Deserialize))]
 pub struct SliceMeta {
+    /// the slice on the canvas, see `atlas_id` for its place in the atlas
     pub rect: Rect,
+    /// index of the slice in `Aseprite::atlas_layout`
     pub atlas_id: usize,
     pub pivot: Option<Vec2>,
     pub nine_patch: Option<Vec4>,
     pub keys: Vec<SliceKeyMeta>,
+    /// text from the slice user data
+    pub user_data: Option<String>,
 }
 
-impl From<&SliceMeta> for Anchor {
-    fn from(value: &SliceMeta) -> Self {
-        match value.pivot {
-            Some(pivot) => {
-                let size = value.rect.size();
-                let uv = (pivot.min(size).max(Vec2::ZERO) / size) - Vec2::new(0.5, 0.5);
-                Anchor(uv * Vec2::new(1.0, -1.0))
-            }
+impl SliceMeta {
+    /// the slice pivot as normalized anchor, centered if the slice has no pivot.
+    pub fn anchor(&self) -> Anchor {
+        match self.pivot {
+            Some(pivot) => pivot_to_anchor(pivot, self.rect.size()),
             None => Anchor::CENTER,
         }
     }
 }
 
+impl From<&SliceMeta> for Anchor {
+    fn from(value: &SliceMeta) -> Self {
+        value.anchor()
+    }
+}
+
+/// converts a pixel pivot inside an area of `size` to a normalized anchor
+pub(crate) fn pivot_to_anchor(pivot: Vec2, size: Vec2) -> Anchor {
+    let uv = (pivot.min(size).max(Vec2::ZERO) / size) - Vec2::new(0.5, 0.5);
+    Anchor(uv * Vec2::new(1.0, -1.0))
+}
+
 #[derive(Default, TypePath)]
 pub struct AsepriteLoader;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AsepriteLoaderSettings {
     pub sampler: ImageSampler,
+    /// builds a [`BitmapFont`] labeled `font` from the file.
+    /// Not available from processed files.
+    pub font: Option<BitmapFontSettings>,
+    /// files with the same group name share one atlas image and layout,
+    /// packed at runtime once they are loaded.
+    #[serde(default)]
+    pub atlas_group: Option<String>,
+    /// additionally builds a 2d array texture labeled `texture_array`,
+    /// with one canvas sized layer per frame.
+    /// Not available from processed files.
+    #[serde(default)]
+    pub texture_array: bool,
+    /// additionally builds an `R8Unorm` texture labeled `index_texture`, holding the
+    /// palette index of every atlas pixel. Shares the atlas layout.
+    /// Not available from processed files.
+    #[serde(default)]
+    pub index_texture: bool,
+    /// composites layers hidden in aseprite as well
+    #[serde(default)]
+    pub include_hidden_layers: bool,
+    /// composites every layer fully opaque, ignoring the layer opacity
+    #[serde(default)]
+    pub ignore_layer_opacity: bool,
+    /// layers ending with this suffix, e.g. `_normal`, are packed into a normal map atlas
+    /// labeled `normal_texture` instead of the color atlas.
+    /// Not available from processed files.
+    #[serde(default)]
+    pub normal_layer_suffix: Option<String>,
+    /// layers ending with this suffix, e.g. `_emissive`, are packed into an emissive atlas
+    /// labeled `emissive_texture` instead of the color atlas.
+    /// Not available from processed files.
+    #[serde(default)]
+    pub emissive_layer_suffix: Option<String>,
+    /// the slice marking the canvas origin of every frame, at its pivot or top left corner.
+    /// Becomes the frame [`Anchor`], so files with different canvas sizes line up.
+    #[serde(default = "default_origin_slice")]
+    pub origin_slice: String,
+    /// a fixed origin in canvas pixels, overriding the origin slice
+    #[serde(default)]
+    pub origin: Option<[u32; 2]>,
+    /// loads only the frames of these tags.
+    /// Tag ranges, frame durations and slice keys refer to the compacted frames.
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+    /// standalone images added as labeled assets, none by default
+    #[serde(default)]
+    pub sub_images: SubImages,
+    /// adds a labeled `tag:{name}` asset per tag, holding only the frames of that tag.
+    /// They share the atlas of the file, load fewer frames with [`Self::tags`].
+    /// Not available from processed files.
+    #[serde(default)]
+    pub tag_assets: bool,
+}
+
+fn default_origin_slice() -> String {
+    "origin".into()
 }
 
 impl Default for AsepriteLoaderSettings {
     fn default() -> Self {
         Self {
             sampler: ImageSampler::nearest(),
+            font: None,
+            atlas_group: None,
+            texture_array: false,
+            index_texture: false,
+            include_hidden_layers: false,
+            ignore_layer_opacity: false,
+            normal_layer_suffix: None,
+            emissive_layer_suffix: None,
+            origin_slice: default_origin_slice(),
+            origin: None,
+            tags: None,
+            tag_assets: false,
+            sub_images: SubImages::default(),
+        }
+    }
+}
+
+/// Standalone images cropped from the atlas, for plugins expecting a plain `Handle<Image>`.
+/// Each one is a copy of its pixels, so they are only added when enabled.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(server: Res<AssetServer>) {
+/// let heart: Handle<Image> = server.load_with_settings(
+///     "ui.aseprite#slice/heart",
+///     |settings: &mut AsepriteLoaderSettings| settings.sub_images.slices = true,
+/// );
+/// let third: Handle<Image> = server.load_with_settings(
+///     "player.aseprite#frame/3",
+///     |settings: &mut AsepriteLoaderSettings| settings.sub_images.frames = true,
+/// );
+/// # }
+/// ```
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+pub struct SubImages {
+    /// every slice as `slice/{name}`
+    pub slices: bool,
+    /// every frame as `frame/{index}`
+    pub frames: bool,
+}
+
+/// adds the enabled [`SubImages`] as labeled assets
+pub(crate) fn add_sub_images(
+    prefix: &str,
+    sub_images: SubImages,
+    slices: &HashMap<String, SliceMeta>,
+    frame_indicies: &[usize],
+    layout: &TextureAtlasLayout,
+    atlas: &Image,
+    load_context: &mut LoadContext<'_>,
+) {
+    if sub_images.slices {
+        for (name, slice) in slices.iter() {
+            let Some(rect) = layout.textures.get(slice.atlas_id) else {
+                continue;
+            };
+            load_context.add_labeled_asset(format!("{prefix}slice/{name}"), crop(atlas, *rect));
+        }
+    }
+    if sub_images.frames {
+        for (frame, index) in frame_indicies.iter().enumerate() {
+            let Some(rect) = layout.textures.get(*index) else {
+                continue;
+            };
+            load_context.add_labeled_asset(format!("{prefix}frame/{frame}"), crop(atlas, *rect));
         }
     }
 }
 
+/// copies a region of the atlas into its own image, clipped to the atlas bounds
+pub(crate) fn crop(atlas: &Image, rect: URect) -> Image {
+    let rect = rect.intersect(URect::from_corners(UVec2::ZERO, atlas.size()));
+    let pixel_size = atlas
+        .texture_descriptor
+        .format
+        .block_copy_size(None)
+        .unwrap_or(4) as usize;
+    let mut data = Vec::with_capacity(rect.width() as usize * rect.height() as usize * pixel_size);
+    for y in rect.min.y..rect.max.y {
+        for x in rect.min.x..rect.max.x {
+            match atlas.pixel_bytes(UVec3::new(x, y, 0)) {
+                Some(pixel) => data.extend_from_slice(pixel),
+                None => data.extend(std::iter::repeat_n(0, pixel_size)),
+            }
+        }
+    }
+    Image {
+        sampler: atlas.sampler.clone(),
+        ..Image::new(
+            Extent3d {
+                width: rect.width(),
+                height: rect.height(),
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            atlas.texture_descriptor.format,
+            RenderAssetUsages::default(),
+        )
+    }
+}
+
 impl AssetLoader for AsepriteLoader {
     type Asset = Aseprite;
     type Settings = AsepriteLoaderSettings;
@@ -121,152 +420,621 @@ impl AssetLoader for AsepriteLoader {
         &self,
         reader: &mut dyn Reader,
         settings: &Self::Settings,
-        load_context: &mut bevy::asset::LoadContext<'_>,
+        load_context: &mut LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader
             .read_to_end(&mut bytes)
             .await
-            .map_err(|_| AsepriteError::ReadError)?;
+            .map_err(|source| AsepriteError::ReadError {
+                path: load_context.path().clone(),
+                source,
+            })?;
 
-        let raw = AsepriteFile::load(&bytes)?;
+        // palette and user data chunks are only kept in the raw file
+        let raw_chunks = parse_raw_file(&bytes).map_err(|err| AsepriteError::LoadingError {
+            path: load_context.path().clone(),
+            source: LoadSpriteError::Parse {
+                message: err.to_string(),
+            },
+        })?;
+        check_cel_layers(&raw_chunks)?;
+        let raw = AsepriteFile::load(&bytes).map_err(|source| AsepriteError::LoadingError {
+            path: load_context.path().clone(),
+            source,
+        })?;
 
-        let mut frame_images = Vec::new();
-        let mut atlas_builder = TextureAtlasBuilder::default();
-        atlas_builder.max_size(UVec2::splat(4096));
+        let (width, height) = raw.size();
+        if raw.frames().is_empty() {
+            return Err(AsepriteError::NoFrames);
+        }
+        let canvas = UVec2::new(width as u32, height as u32);
+        if canvas.min_element() == 0 || canvas.max_element() > MAX_ATLAS_SIZE {
+            return Err(AsepriteError::InvalidCanvasSize {
+                width: canvas.x,
+                height: canvas.y,
+                max: MAX_ATLAS_SIZE,
+            });
+        }
+
+        let suffixes = [
+            settings.normal_layer_suffix.as_deref(),
+            settings.emissive_layer_suffix.as_deref(),
+        ];
+        let is_color = |name: &str| {
+            suffixes
+                .iter()
+                .flatten()
+                .all(|suffix| !name.ends_with(suffix))
+        };
+
+        // one canvas sized buffer per frame, from the layers passing `include`
+        let images = composite_frames(&raw, settings, is_color)?;
+        let normal_images = match suffixes[0] {
+            Some(suffix) => Some(composite_frames(&raw, settings, |name| {
+                name.ends_with(suffix)
+            })?),
+            None => None,
+        };
+        let emissive_images = match suffixes[1] {
+            Some(suffix) => Some(composite_frames(&raw, settings, |name| {
+                name.ends_with(suffix)
+            })?),
+            None => None,
+        };
+
+        // ----------------------------- slices
+        let mut slice_user_data = slice_user_data(&raw_chunks);
+        let slices = raw
+            .slices()
+            .iter()
+            .map(|slice| SliceParts {
+                name: slice.name.into(),
+                keys: slice
+                    .slice_keys
+                    .iter()
+                    .map(|key| {
+                        let min = Vec2::new(key.x as f32, key.y as f32);
+                        let max = min + Vec2::new(key.width as f32, key.height as f32);
+                        SliceKeyMeta {
+                            frame: key.frame_number as usize,
+                            rect: Rect::from_corners(min, max),
+                            pivot: key.pivot.map(|p| Vec2::new(p.x as f32, p.y as f32)),
+                            nine_patch: key.nine_patch.map(|np| {
+                                Vec4::new(
+                                    np.x as f32,
+                                    np.y as f32,
+                                    np.width as f32,
+                                    np.height as f32,
+                                )
+                            }),
+                        }
+                    })
+                    .collect(),
+                user_data: slice_user_data.remove(slice.name),
+            })
+            .collect();
+
+        // ---------------------------- tags
+        let last_frame = raw.frames().len().saturating_sub(1).min(u16::MAX as usize) as u16;
+        let mut tags = HashMap::new();
+        raw.tags().iter().for_each(|tag| {
+            let direction = match tag.direction {
+                AnimationDirection::Unknown(direction) => {
+                    warn!(
+                        "tag {} has unknown direction {direction}, playing forward",
+                        tag.name
+                    );
+                    AnimationDirection::Forward
+                }
+                direction => direction,
+            };
+            tags.insert(
+                tag.name.clone(),
+                TagMeta {
+                    direction,
+                    range: clamp_tag_range(&tag.name, tag.range.clone(), last_frame),
+                    repeat: tag.repeat.unwrap_or(0),
+                },
+            );
+        });
+
+        // ---------------------------- frames
+        let frame_durations = raw
+            .frames()
+            .iter()
+            .map(|frame| std::time::Duration::from_millis(u64::from(frame.duration)))
+            .collect();
+
+        AsepriteParts {
+            canvas,
+            images,
+            frame_durations,
+            tags,
+            slices,
+            palette: file_palette(&raw_chunks),
+            normal_images,
+            emissive_images,
+        }
+        .assemble(settings, load_context)
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite", "ase"]
+    }
+}
+
+/// A slice with all its keys, before it is added to the atlas.
+#[derive(Clone)]
+pub(crate) struct SliceParts {
+    pub name: String,
+    pub keys: Vec<SliceKeyMeta>,
+    pub user_data: Option<String>,
+}
+
+/// Source format independent parts of an [`Aseprite`].
+/// Frames are canvas sized rgba8 buffers.
+#[derive(Clone)]
+pub(crate) struct AsepriteParts {
+    pub canvas: UVec2,
+    pub images: Vec<Vec<u8>>,
+    pub frame_durations: Vec<std::time::Duration>,
+    pub tags: HashMap<String, TagMeta>,
+    pub slices: Vec<SliceParts>,
+    pub palette: Vec<Color>,
+    /// frames of the normal map layers
+    pub normal_images: Option<Vec<Vec<u8>>>,
+    /// frames of the emissive layers
+    pub emissive_images: Option<Vec<Vec<u8>>>,
+}
 
-        let mut images = Vec::new();
+impl AsepriteParts {
+    /// packs the frames into an atlas and adds all labeled assets
+    pub(crate) fn assemble(
+        mut self,
+        settings: &AsepriteLoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Aseprite, AsepriteError> {
+        if let Some(tags) = settings.tags.as_ref() {
+            self.retain_tags(tags);
+        }
 
-        for (index, _frame) in raw.frames().iter().enumerate() {
-            let (width, height) = raw.size();
-            let mut buffer = vec![0; width as usize * height as usize * 4];
+        let aseprite = self.assemble_atlas(settings, load_context)?;
+        if settings.tag_assets {
+            for name in aseprite.tags.keys() {
+                if let Some(view) = aseprite.tag_view(name) {
+                    load_context.add_labeled_asset(format!("tag:{name}"), view);
+                }
+            }
+        }
+        Ok(aseprite)
+    }
 
-            let _hash = raw.combined_frame_image(index, buffer.as_mut_slice())?;
+    /// keeps only the frames of the given tags, remapping tag ranges and slice keys
+    fn retain_tags(&mut self, names: &[String]) {
+        for name in names.iter() {
+            if !self.tags.contains_key(name) {
+                warn!("tag {name} does not exist and is not loaded");
+            }
+        }
+        self.tags.retain(|name, _| names.contains(name));
 
-            let image = Image {
+        let mut keep = vec![false; self.frame_durations.len()];
+        for tag in self.tags.values() {
+            for frame in tag.range.clone() {
+                if let Some(keep) = keep.get_mut(frame as usize) {
+                    *keep = true;
+                }
+            }
+        }
+
+        // new index of every old frame, or of the next kept frame
+        let mut remap = Vec::with_capacity(keep.len() + 1);
+        let mut next = 0;
+        for kept in keep.iter() {
+            remap.push(next);
+            next += *kept as usize;
+        }
+        remap.push(next);
+
+        let retain = |frames: &mut Vec<Vec<u8>>| {
+            let mut kept = keep.iter();
+            frames.retain(|_| kept.next().cloned().unwrap_or_default());
+        };
+        retain(&mut self.images);
+        if let Some(frames) = self.normal_images.as_mut() {
+            retain(frames);
+        }
+        if let Some(frames) = self.emissive_images.as_mut() {
+            retain(frames);
+        }
+        let mut kept = keep.iter();
+        self.frame_durations
+            .retain(|_| kept.next().cloned().unwrap_or_default());
+
+        let last = next.saturating_sub(1);
+        let new_index = |frame: usize| remap[frame.min(keep.len())].min(last);
+
+        for tag in self.tags.values_mut() {
+            let start = new_index(*tag.range.start() as usize) as u16;
+            let end = new_index(*tag.range.end() as usize) as u16;
+            tag.range = start..=end;
+        }
+
+        for slice in self.slices.iter_mut() {
+            for key in slice.keys.iter_mut() {
+                key.frame = new_index(key.frame);
+            }
+        }
+    }
+
+    /// packs the frames into an atlas and adds the labeled textures
+    fn assemble_atlas(
+        self,
+        settings: &AsepriteLoaderSettings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Aseprite, AsepriteError> {
+        if self.images.is_empty() {
+            return Err(AsepriteError::NoFrames);
+        }
+
+        let mut frame_images = Vec::new();
+        let mut atlas_builder = TextureAtlasBuilder::default();
+        atlas_builder.max_size(UVec2::splat(MAX_ATLAS_SIZE));
+
+        let images = self
+            .images
+            .into_iter()
+            .map(|buffer| Image {
                 sampler: settings.sampler.clone(),
                 ..Image::new(
                     Extent3d {
-                        width: width as u32,
-                        height: height as u32,
+                        width: self.canvas.x,
+                        height: self.canvas.y,
                         depth_or_array_layers: 1,
                     },
                     TextureDimension::D2,
-                    buffer.clone(),
+                    buffer,
                     TextureFormat::Rgba8UnormSrgb,
                     RenderAssetUsages::default(),
                 )
-            };
-            images.push(image);
-        }
+            })
+            .collect::<Vec<_>>();
 
-        for image in images.iter() {
+        // stable keys, so identical input packs into identical output
+        for (index, image) in images.iter().enumerate() {
             let handle_id = AssetId::Uuid {
-                uuid: Uuid::new_v4(),
+                uuid: Uuid::from_u128(index as u128),
             };
 
             frame_images.push(handle_id);
-            atlas_builder.add_texture(Some(handle_id), &image);
+            atlas_builder.add_texture(Some(handle_id), image);
         }
 
+        // ----------------------------- texture array
+        let texture_array = match settings.texture_array && !images.is_empty() {
+            true => {
+                let data = images
+                    .iter()
+                    .flat_map(|image| image.data.iter().flatten().copied())
+                    .collect();
+                let array = Image {
+                    sampler: settings.sampler.clone(),
+                    texture_view_descriptor: Some(TextureViewDescriptor {
+                        dimension: Some(TextureViewDimension::D2Array),
+                        ..default()
+                    }),
+                    ..Image::new(
+                        Extent3d {
+                            width: self.canvas.x,
+                            height: self.canvas.y,
+                            depth_or_array_layers: images.len() as u32,
+                        },
+                        TextureDimension::D2,
+                        data,
+                        TextureFormat::Rgba8UnormSrgb,
+                        RenderAssetUsages::default(),
+                    )
+                };
+                Some(load_context.add_labeled_asset("texture_array".into(), array))
+            }
+            false => None,
+        };
+
         // ----------------------------- atlas
-        let (mut layout, source, image) = atlas_builder.build()?;
+        let (mut layout, source, image) =
+            atlas_builder
+                .build()
+                .map_err(|source| AsepriteError::TextureAtlasError {
+                    path: load_context.path().clone(),
+                    source,
+                })?;
 
         let frame_indicies = frame_images
             .iter()
-            .map(|id| source.texture_ids.get(id).cloned().unwrap())
-            .collect::<Vec<_>>();
+            .enumerate()
+            .map(|(frame, id)| {
+                source
+                    .texture_ids
+                    .get(id)
+                    .cloned()
+                    .ok_or(AsepriteError::MissingAtlasFrame(frame))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // ----------------------------- auxiliary atlases
+        let normal_texture = self.normal_images.map(|frames| {
+            let atlas = aux_atlas(
+                &layout,
+                &frame_indicies,
+                frames,
+                self.canvas,
+                settings,
+                false,
+            );
+            load_context.add_labeled_asset("normal_texture".into(), atlas)
+        });
+        let emissive_texture = self.emissive_images.map(|frames| {
+            let atlas = aux_atlas(
+                &layout,
+                &frame_indicies,
+                frames,
+                self.canvas,
+                settings,
+                true,
+            );
+            load_context.add_labeled_asset("emissive_texture".into(), atlas)
+        });
 
         // ----------------------------- slices
         let mut slices = HashMap::new();
-        raw.slices().iter().for_each(|slice| {
-            let slice_key = slice.slice_keys.first().unwrap();
-
-            let min = Vec2::new(slice_key.x as f32, slice_key.y as f32);
-            let max = min + Vec2::new(slice_key.width as f32, slice_key.height as f32);
-
-            let pivot = match slice_key.pivot {
-                Some(pivot) => Some(Vec2::new(pivot.x as f32, pivot.y as f32)),
-                None => None,
+        for slice in self.slices.iter() {
+            let Some(slice_key) = slice.keys.first() else {
+                continue;
             };
 
-            let nine_patch = match slice_key.nine_patch {
-                Some(nine_patch) => Some(Vec4::new(
-                    nine_patch.x as f32,
-                    nine_patch.y as f32,
-                    nine_patch.width as f32,
-                    nine_patch.height as f32,
-                )),
-                None => None,
-            };
-
-            let layout_id = layout.add_texture(URect::from_corners(min.as_uvec2(), max.as_uvec2()));
-
-            let mut keys = Vec::new();
-            for key in &slice.slice_keys {
-                let k_min = Vec2::new(key.x as f32, key.y as f32);
-                let k_max = k_min + Vec2::new(key.width as f32, key.height as f32);
-
-                let k_pivot = key.pivot.map(|p| Vec2::new(p.x as f32, p.y as f32));
-
-                let k_nine_patch = key.nine_patch.map(|np| {
-                    Vec4::new(np.x as f32, np.y as f32, np.width as f32, np.height as f32)
-                });
-
-                keys.push(SliceKeyMeta {
-                    frame: key.frame_number as usize,
-                    rect: Rect::from_corners(k_min, k_max),
-                    pivot: k_pivot,
-                    nine_patch: k_nine_patch,
-                });
-            }
+            let layout_id = layout.add_texture(URect::from_corners(
+                slice_key.rect.min.as_uvec2(),
+                slice_key.rect.max.as_uvec2(),
+            ));
 
             slices.insert(
-                slice.name.into(),
+                slice.name.clone(),
                 SliceMeta {
-                    rect: Rect::from_corners(min, max),
+                    rect: slice_key.rect,
                     atlas_id: layout_id,
-                    pivot,
-                    nine_patch,
-                    keys,
+                    pivot: slice_key.pivot,
+                    nine_patch: slice_key.nine_patch,
+                    keys: slice.keys.clone(),
+                    user_data: slice.user_data.clone(),
                 },
             );
+        }
+
+        // ----------------------------- font
+        let font = settings.font.as_ref().map(|font_settings| {
+            BitmapFont::build(font_settings, &slices, &frame_indicies, &mut layout)
         });
 
+        // ----------------------------- palette
+        let palette_texture = match self.palette.is_empty() {
+            true => None,
+            false => {
+                Some(load_context.add_labeled_asset("palette".into(), palette_image(&self.palette)))
+            }
+        };
+        let index_texture = match settings.index_texture && !self.palette.is_empty() {
+            true => Some(
+                load_context
+                    .add_labeled_asset("index_texture".into(), index_image(&image, &self.palette)),
+            ),
+            false => None,
+        };
+
+        add_sub_images(
+            "",
+            settings.sub_images,
+            &slices,
+            &frame_indicies,
+            &layout,
+            &image,
+            load_context,
+        );
+
+        let atlas_range = 0..layout.len();
         let atlas_layout = load_context.add_labeled_asset("atlas_layout".into(), layout);
         let atlas_image = load_context.add_labeled_asset("atlas_texture".into(), image);
 
-        // ---------------------------- tags
-        let mut tags = HashMap::new();
-        raw.tags().iter().for_each(|tag| {
-            tags.insert(
-                tag.name.clone(),
-                TagMeta {
-                    direction: tag.direction,
-                    range: tag.range.clone(),
-                    repeat: tag.repeat.unwrap_or(0),
+        if let Some(font) = font {
+            load_context.add_labeled_asset(
+                "font".into(),
+                BitmapFont {
+                    atlas_layout: atlas_layout.clone(),
+                    atlas_image: atlas_image.clone(),
+                    ..font
                 },
             );
-        });
+        }
 
-        // ---------------------------- frames
-        let frame_durations = raw
-            .frames()
+        // ---------------------------- frame anchors
+        // a fixed origin wins over the origin slice, which wins over pivot slices
+        let canvas = self.canvas.as_vec2();
+        let origin_slice = self
+            .slices
             .iter()
-            .map(|frame| std::time::Duration::from_millis(u64::from(frame.duration)))
-            .collect();
+            .find(|slice| slice.name == settings.origin_slice);
+        let frame_anchors = match (settings.origin, origin_slice) {
+            (Some([x, y]), _) => {
+                let anchor = pivot_to_anchor(Vec2::new(x as f32, y as f32), canvas).0;
+                vec![Some(anchor); self.frame_durations.len()]
+            }
+            (None, origin_slice) => (0..self.frame_durations.len())
+                .map(|frame| {
+                    let origin = origin_slice.and_then(|slice| {
+                        let key = slice.keys.iter().rev().find(|key| key.frame <= frame)?;
+                        Some(key.rect.min + key.pivot.unwrap_or_default())
+                    });
+                    // frames without an origin follow the first slice carrying a pivot
+                    let pivot = origin.or_else(|| {
+                        self.slices.iter().find_map(|slice| {
+                            let key = slice.keys.iter().rev().find(|key| key.frame <= frame)?;
+                            Some(key.rect.min + key.pivot?)
+                        })
+                    });
+                    pivot.map(|pivot| pivot_to_anchor(pivot, canvas).0)
+                })
+                .collect(),
+        };
 
         Ok(Aseprite {
             slices,
-            tags,
-            frame_durations,
+            tags: self.tags,
+            frame_durations: self.frame_durations,
             atlas_layout,
             atlas_image,
+            texture_array,
+            palette: self.palette,
+            palette_texture,
+            index_texture,
+            normal_texture,
+            emissive_texture,
             frame_indicies,
+            atlas_group: settings.atlas_group.clone(),
+            atlas_range,
+            sub_images: settings.sub_images,
+            frame_anchors,
         })
     }
+}
 
-    fn extensions(&self) -> &[&str] {
-        &["aseprite", "ase"]
+/// places canvas sized frames at their rect in the color atlas
+fn aux_atlas(
+    layout: &TextureAtlasLayout,
+    frame_indicies: &[usize],
+    frames: Vec<Vec<u8>>,
+    canvas: UVec2,
+    settings: &AsepriteLoaderSettings,
+    is_srgb: bool,
+) -> Image {
+    let row = canvas.x as usize * 4;
+    let stride = layout.size.x as usize * 4;
+    let mut data = vec![0; stride * layout.size.y as usize];
+
+    for (frame, buffer) in frames.iter().enumerate() {
+        let Some(rect) = frame_indicies
+            .get(frame)
+            .and_then(|index| layout.textures.get(*index))
+        else {
+            continue;
+        };
+        // the frame rect in the color atlas, clipped to the atlas and the canvas
+        let max = rect.max.min(layout.size).min(rect.min + canvas);
+        let size = max.saturating_sub(rect.min).as_usizevec2();
+        let width = size.x * 4;
+        for y in 0..size.y {
+            let Some(source) = buffer.get(y * row..y * row + width) else {
+                break;
+            };
+            let start = (rect.min.y as usize + y) * stride + rect.min.x as usize * 4;
+            data[start..start + width].copy_from_slice(source);
+        }
+    }
+
+    Image {
+        sampler: settings.sampler.clone(),
+        ..Image::new(
+            Extent3d {
+                width: layout.size.x,
+                height: layout.size.y,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            match is_srgb {
+                true => TextureFormat::Rgba8UnormSrgb,
+                false => TextureFormat::Rgba8Unorm,
+            },
+            RenderAssetUsages::default(),
+        )
+    }
+}
+
+/// the palette chunks of the file, later chunks override earlier entries
+fn file_palette(raw: &RawFile) -> Vec<Color> {
+    let mut palette = Vec::new();
+    for frame in raw.frames.iter() {
+        for chunk in frame.chunks.iter() {
+            let Chunk::Palette(chunk) = chunk else {
+                continue;
+            };
+            let first = *chunk.indices.start() as usize;
+            if palette.len() < first + chunk.entries.len() {
+                palette.resize(first + chunk.entries.len(), Color::NONE);
+            }
+            for (index, entry) in chunk.entries.iter().enumerate() {
+                palette[first + index] = Color::srgba_u8(
+                    entry.color.red,
+                    entry.color.green,
+                    entry.color.blue,
+                    entry.color.alpha,
+                );
+            }
+        }
+    }
+    palette
+}
+
+/// aseprite stores slice user data in a chunk following the slice chunk
+/// Orders a tag range and clamps it to `last_frame`, warning if it was out of range.
+pub(crate) fn clamp_tag_range(
+    tag: &str,
+    range: std::ops::RangeInclusive<u16>,
+    last_frame: u16,
+) -> std::ops::RangeInclusive<u16> {
+    let (start, end) = (*range.start(), *range.end());
+    if start > end || end > last_frame {
+        warn!("tag {tag} frames {start}..={end} are out of range, clamped to 0..={last_frame}");
+    }
+    let end = end.min(last_frame);
+    start.min(end)..=end
+}
+
+/// aseprite-loader panics on a cel one past the last layer, reject those before parsing
+fn check_cel_layers(raw: &RawFile) -> Result<(), AsepriteError> {
+    let layers = raw
+        .frames
+        .iter()
+        .flat_map(|frame| frame.chunks.iter())
+        .filter(|chunk| matches!(chunk, Chunk::Layer(_)))
+        .count();
+    for (frame, raw_frame) in raw.frames.iter().enumerate() {
+        for chunk in raw_frame.chunks.iter() {
+            if let Chunk::Cel(cel) = chunk {
+                let layer = usize::from(cel.layer_index);
+                if layer >= layers {
+                    return Err(AsepriteError::MissingLayer { frame, layer });
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn slice_user_data(raw: &RawFile) -> HashMap<String, String> {
+    let mut user_data = HashMap::new();
+    for frame in raw.frames.iter() {
+        let mut last_slice = None;
+        for chunk in frame.chunks.iter() {
+            match chunk {
+                Chunk::Slice(slice) => last_slice = Some(slice.name),
+                Chunk::UserData(data) => {
+                    if let (Some(name), Some(text)) = (last_slice.take(), data.text) {
+                        user_data.insert(name.to_string(), text.to_string());
+                    }
+                }
+                _ => last_slice = None,
+            }
+        }
     }
+    user_data
 }
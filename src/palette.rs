@@ -0,0 +1,219 @@
+use crate::{
+    animation::{render_animation, AnimationState, RenderAnimation},
+    loader::Aseprite,
+};
+use bevy::{
+    asset::{embedded_asset, uuid_handle, RenderAssetUsages},
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{AsBindGroup, Extent3d, TextureDimension, TextureFormat},
+    shader::ShaderRef,
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin},
+    ui::UiSystems,
+};
+
+/// Renders [`PaletteSwapMaterial`] animations on 2d meshes and ui nodes.
+///
+/// Not part of [`AsepriteUltraPlugin`](crate::prelude::AsepriteUltraPlugin),
+/// since it requires the render plugins.
+pub struct PaletteSwapPlugin;
+
+impl Plugin for PaletteSwapPlugin {
+    fn build(&self, app: &mut App) {
+        embedded_asset!(app, "palette_swap.wgsl");
+        embedded_asset!(app, "palette_swap_ui.wgsl");
+        app.world_mut()
+            .resource_mut::<Assets<Image>>()
+            .insert(&PLACEHOLDER_PALETTE, palette_image(&[]))
+            .expect("placeholder palette is a uuid handle");
+        app.add_plugins(Material2dPlugin::<PaletteSwapMaterial>::default());
+        app.add_plugins(UiMaterialPlugin::<PaletteSwapMaterial>::default());
+        app.add_systems(
+            PostUpdate,
+            render_animation::<MaterialNode<PaletteSwapMaterial>>.before(UiSystems::Prepare),
+        );
+        app.add_systems(
+            PostUpdate,
+            render_animation::<MeshMaterial2d<PaletteSwapMaterial>>,
+        );
+    }
+}
+
+/// A single transparent texel, the palette of [`PaletteSwapMaterial`] until the file palette is loaded.
+/// Files without a palette keep it.
+pub const PLACEHOLDER_PALETTE: Handle<Image> = uuid_handle!("e678f5b8-ba02-414c-adf0-8b2c753f0d00");
+
+/// Builds a 1d palette texture, one texel per color.
+/// Use it to create palettes for [`PaletteSwapMaterial`].
+pub fn palette_image(colors: &[Color]) -> Image {
+    let data = colors
+        .iter()
+        .flat_map(|color| color.to_srgba().to_u8_array())
+        .collect();
+    Image::new(
+        Extent3d {
+            width: colors.len().max(1) as u32,
+            height: 1,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D1,
+        match colors.is_empty() {
+            true => vec![0; 4],
+            false => data,
+        },
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Maps every atlas pixel to its palette index.
+/// Colors missing in the palette map to the first transparent entry, or zero, with a warning.
+/// Duplicate palette colors resolve to their first index.
+pub(crate) fn index_image(atlas: &Image, palette: &[Color]) -> Image {
+    let mut lookup = HashMap::new();
+    for (index, color) in palette.iter().enumerate().take(256) {
+        lookup
+            .entry(color.to_srgba().to_u8_array())
+            .or_insert(index as u8);
+    }
+    if palette.len() > 256 {
+        warn!("palette has more than 256 colors, extra colors are not indexed");
+    }
+
+    let transparent = palette
+        .iter()
+        .take(256)
+        .position(|color| color.alpha() == 0.0)
+        .unwrap_or_default() as u8;
+
+    let mut missing = 0;
+    let data = atlas
+        .data
+        .as_deref()
+        .unwrap_or_default()
+        .chunks_exact(4)
+        .map(|pixel| {
+            let pixel = [pixel[0], pixel[1], pixel[2], pixel[3]];
+            match pixel[3] {
+                0 => transparent,
+                _ => lookup.get(&pixel).cloned().unwrap_or_else(|| {
+                    missing += 1;
+                    transparent
+                }),
+            }
+        })
+        .collect();
+    if missing > 0 {
+        warn!("{missing} pixel colors are missing in the palette, indexed as transparent");
+    }
+
+    Image::new(
+        atlas.texture_descriptor.size,
+        TextureDimension::D2,
+        data,
+        TextureFormat::R8Unorm,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// Renders an animation from its index texture and a swappable palette.
+///
+/// Load the file with [`AsepriteLoaderSettings::index_texture`](crate::prelude::AsepriteLoaderSettings::index_texture)
+/// enabled. Leave `palette` at its default to use the file palette,
+/// or set it to any palette texture to recolor the animation.
+/// Pixels with colors missing in the file palette are indexed as transparent.
+///
+/// # Examples
+/// ```
+/// # use bevy::{color::palettes::css::RED, prelude::*};
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(
+/// #     mut cmd: Commands,
+/// #     server: Res<AssetServer>,
+/// #     mut images: ResMut<Assets<Image>>,
+/// #     mut meshes: ResMut<Assets<Mesh>>,
+/// #     mut materials: ResMut<Assets<PaletteSwapMaterial>>,
+/// # ) {
+/// let red = images.add(palette_image(&[Color::NONE, Color::BLACK, RED.into()]));
+/// cmd.spawn((
+///     AseAnimation {
+///         aseprite: server.load_with_settings(
+///             "enemy.aseprite",
+///             |settings: &mut AsepriteLoaderSettings| settings.index_texture = true,
+///         ),
+///         animation: Animation::tag("walk"),
+///     },
+///     Mesh2d(meshes.add(Rectangle::from_size(Vec2::splat(32.0)))),
+///     MeshMaterial2d(materials.add(PaletteSwapMaterial {
+///         palette: red,
+///         ..default()
+///     })),
+/// ));
+/// # }
+/// ```
+#[derive(AsBindGroup, Asset, TypePath, Debug, Clone)]
+pub struct PaletteSwapMaterial {
+    #[texture(0)]
+    pub index_texture: Handle<Image>,
+    #[texture(1, dimension = "1d")]
+    pub palette: Handle<Image>,
+    #[uniform(2)]
+    pub texture_min: UVec2,
+    #[uniform(3)]
+    pub texture_max: UVec2,
+}
+
+impl Default for PaletteSwapMaterial {
+    fn default() -> Self {
+        Self {
+            index_texture: Handle::default(),
+            palette: PLACEHOLDER_PALETTE,
+            texture_min: UVec2::ZERO,
+            texture_max: UVec2::ZERO,
+        }
+    }
+}
+
+impl Material2d for PaletteSwapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_aseprite_ultra/palette_swap.wgsl".into()
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl UiMaterial for PaletteSwapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_aseprite_ultra/palette_swap_ui.wgsl".into()
+    }
+}
+
+impl RenderAnimation for PaletteSwapMaterial {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_animation(
+        &mut self,
+        aseprite: &Aseprite,
+        state: &AnimationState,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let (Some(index_texture), Some(atlas_layout)) =
+            (aseprite.index_texture.as_ref(), extra.get(&aseprite.atlas_layout))
+        else {
+            return;
+        };
+        let index = aseprite.get_atlas_index(usize::from(state.current_frame));
+        let Some(rect) = atlas_layout.textures.get(index) else {
+            return;
+        };
+
+        self.index_texture = index_texture.clone();
+        if self.palette == PLACEHOLDER_PALETTE {
+            if let Some(palette) = aseprite.palette_texture.as_ref() {
+                self.palette = palette.clone();
+            }
+        }
+        self.texture_min = rect.min;
+        self.texture_max = rect.max;
+    }
+}
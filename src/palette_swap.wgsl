@@ -0,0 +1,17 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var index_texture: texture_2d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var palette: texture_1d<f32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(2) var<uniform> texture_min: vec2u;
+@group(#{MATERIAL_BIND_GROUP}) @binding(3) var<uniform> texture_max: vec2u;
+
+@fragment
+fn fragment(
+    v: VertexOutput,
+) -> @location(0) vec4<f32> {
+    let size = vec2f(texture_max - texture_min);
+    let pixel = min(texture_min + vec2u(v.uv * size), texture_max - vec2u(1u));
+    let index = u32(round(textureLoad(index_texture, pixel, 0).r * 255.0));
+    let count = textureDimensions(palette);
+    return textureLoad(palette, min(index, count - 1u), 0);
+}
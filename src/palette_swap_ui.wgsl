@@ -0,0 +1,17 @@
+#import bevy_ui::ui_vertex_output::UiVertexOutput
+
+@group(1) @binding(0) var index_texture: texture_2d<f32>;
+@group(1) @binding(1) var palette: texture_1d<f32>;
+@group(1) @binding(2) var<uniform> texture_min: vec2u;
+@group(1) @binding(3) var<uniform> texture_max: vec2u;
+
+@fragment
+fn fragment(
+    v: UiVertexOutput,
+) -> @location(0) vec4<f32> {
+    let size = vec2f(texture_max - texture_min);
+    let pixel = min(texture_min + vec2u(v.uv * size), texture_max - vec2u(1u));
+    let index = u32(round(textureLoad(index_texture, pixel, 0).r * 255.0));
+    let count = textureDimensions(palette);
+    return textureLoad(palette, min(index, count - 1u), 0);
+}
@@ -2,6 +2,7 @@ use std::io::Cursor;
 
 use bevy::{
     asset::{
+        meta::{AssetAction, AssetMeta, AssetMetaDyn},
         processor::LoadTransformAndSave,
         saver::{AssetSaver, SavedAsset},
         transformer::IdentityAssetTransformer,
@@ -16,7 +17,9 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     error::AsepriteError,
-    loader::{Aseprite, AsepriteLoader},
+    loader::{add_sub_images, Aseprite, AsepriteLoader, AsepriteLoaderSettings},
+    palette::palette_image,
+    sheet::{load_sheet, png_data_uri, sheet_json},
 };
 
 pub struct AsepriteProcessorPlugin;
@@ -55,13 +58,68 @@ struct AsepriteDeserialize {
     pub atlas_layout: TextureAtlasLayout,
 }
 
+/// Output format of the asset processor
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq)]
+pub enum ProcessedFormat {
+    /// msgpack metadata followed by a qoi atlas
+    #[default]
+    Binary,
+    /// aseprite sprite sheet json, with the png atlas embedded as data uri in `meta.image`
+    JsonSheet,
+}
+
+/// Magic bytes at the start of the binary processed format
+const MAGIC: &[u8; 4] = b"ASEU";
+
+/// Version of the binary processed format. Bump on any layout or schema change.
+///
+/// Files of another version fail to load with
+/// [`AsepriteError::ProcessedVersionMismatch`] until they are processed again.
+pub const PROCESSED_FORMAT_VERSION: u16 = 1;
+
+/// header: magic, format version, msgpack length
+const HEADER_SIZE: usize = MAGIC.len() + 2 + 8;
+
+/// Image format of the atlas in the binary processed format
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+pub enum AtlasImageFormat {
+    /// small and fast to decode
+    #[default]
+    Qoi,
+    /// smallest, slowest to decode
+    Png,
+    /// uncompressed rgba8, largest but uploads without decoding.
+    /// The only format supporting mipmaps.
+    Ktx2,
+}
+
+impl AtlasImageFormat {
+    fn bevy_format(self) -> bevy::prelude::ImageFormat {
+        match self {
+            AtlasImageFormat::Qoi => bevy::prelude::ImageFormat::Qoi,
+            AtlasImageFormat::Png => bevy::prelude::ImageFormat::Png,
+            AtlasImageFormat::Ktx2 => bevy::prelude::ImageFormat::Ktx2,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct AsepriteSaverSettings {
+    pub format: ProcessedFormat,
+    #[serde(default)]
+    pub image_format: AtlasImageFormat,
+    /// generates a full mip chain, only with [`AtlasImageFormat::Ktx2`]
+    #[serde(default)]
+    pub generate_mipmaps: bool,
+}
+
 #[derive(TypePath)]
 struct AsepriteSaver;
 
 impl AssetSaver for AsepriteSaver {
     type Asset = Aseprite;
 
-    type Settings = ();
+    type Settings = AsepriteSaverSettings;
 
     type OutputLoader = ProcessedAsepriteLoader;
 
@@ -71,7 +129,7 @@ impl AssetSaver for AsepriteSaver {
         &self,
         writer: &mut bevy::asset::io::Writer,
         asset: bevy::asset::saver::SavedAsset<'_, Self::Asset>,
-        _settings: &Self::Settings,
+        settings: &Self::Settings,
     ) -> Result<<Self::OutputLoader as bevy::asset::AssetLoader>::Settings, Self::Error> {
         let texture_atlas_layout: SavedAsset<TextureAtlasLayout> = asset
             .get_labeled("atlas_layout")
@@ -80,47 +138,106 @@ impl AssetSaver for AsepriteSaver {
             .get_labeled("atlas_texture")
             .expect("atlas_texture should exist");
 
-        let aseprite_ser = AsepriteSerialize {
-            aseprite: asset.get(),
-            atlas_layout: texture_atlas_layout.get(),
-        };
-
-        let msgpack_buf = rmp_serde::to_vec(&aseprite_ser)?;
+        let bytes = write_processed(
+            asset.get(),
+            texture_atlas_layout.get(),
+            atlas_texture.get(),
+            settings,
+        )?;
 
-        // Write length of msgpack segment
         writer
-            .write_all(&((msgpack_buf.len() as u64).to_be_bytes()))
+            .write_all(&bytes)
             .await
             .map_err(|_| AsepriteError::WriteError)?;
 
-        // Write msgpack itself
-        writer
-            .write_all(&msgpack_buf)
-            .await
-            .map_err(|_| AsepriteError::WriteError)?;
+        Ok(loader_settings(atlas_texture.get(), settings))
+    }
+}
 
-        let mut image_buf = Vec::new();
-        let mut image_write = Cursor::new(&mut image_buf);
+/// Serializes a loaded [`Aseprite`] into the processed format of `settings`,
+/// like the asset processor does. Load the output with the [`processed_meta`] next to it.
+pub fn write_processed(
+    aseprite: &Aseprite,
+    atlas_layout: &TextureAtlasLayout,
+    atlas_texture: &Image,
+    settings: &AsepriteSaverSettings,
+) -> Result<Vec<u8>, AsepriteError> {
+    if settings.format == ProcessedFormat::JsonSheet {
+        let mut png_buf = Vec::new();
+        let dynamic = dynamic_image(atlas_texture)?;
+        dynamic.write_to(&mut Cursor::new(&mut png_buf), ImageFormat::Png)?;
+
+        let json = sheet_json(aseprite, atlas_layout, png_data_uri(&png_buf))?;
+        return Ok(json.into_bytes());
+    }
 
-        let dynamic = atlas_texture
-            .clone()
-            .try_into_dynamic()
-            .expect("Atlas image should be of a supported image type");
-        dynamic.write_to(&mut image_write, ImageFormat::Qoi)?;
+    let aseprite_ser = AsepriteSerialize {
+        aseprite,
+        atlas_layout,
+    };
 
-        writer
-            .write_all(&image_buf)
-            .await
-            .map_err(|_| AsepriteError::WriteError)?;
+    let msgpack_buf = rmp_serde::to_vec(&aseprite_ser)?;
 
-        Ok(ImageLoaderSettings {
-            format: ImageFormatSetting::Format(bevy::prelude::ImageFormat::Qoi),
-            is_srgb: atlas_texture.texture_descriptor.format.is_srgb(),
-            sampler: atlas_texture.sampler.clone(),
-            asset_usage: atlas_texture.asset_usage,
-            texture_format: None,
-            array_layout: None,
-        })
+    // header, length of the msgpack segment, msgpack itself
+    let mut out = Vec::with_capacity(HEADER_SIZE + msgpack_buf.len());
+    out.extend_from_slice(MAGIC);
+    out.extend_from_slice(&PROCESSED_FORMAT_VERSION.to_be_bytes());
+    out.extend_from_slice(&(msgpack_buf.len() as u64).to_be_bytes());
+    out.extend_from_slice(&msgpack_buf);
+
+    if settings.generate_mipmaps && settings.image_format != AtlasImageFormat::Ktx2 {
+        warn!("aseprite atlas mipmaps are only generated for ktx2");
+    }
+
+    let mut image_buf = Vec::new();
+    let mut image_write = Cursor::new(&mut image_buf);
+
+    let dynamic = dynamic_image(atlas_texture)?;
+
+    match settings.image_format {
+        AtlasImageFormat::Qoi => dynamic.write_to(&mut image_write, ImageFormat::Qoi)?,
+        AtlasImageFormat::Png => dynamic.write_to(&mut image_write, ImageFormat::Png)?,
+        AtlasImageFormat::Ktx2 => {
+            image_buf = write_ktx2(
+                &dynamic.into_rgba8(),
+                atlas_texture.texture_descriptor.format.is_srgb(),
+                settings.generate_mipmaps,
+            )
+        }
+    }
+
+    out.extend_from_slice(&image_buf);
+    Ok(out)
+}
+
+fn dynamic_image(atlas_texture: &Image) -> Result<image::DynamicImage, AsepriteError> {
+    atlas_texture
+        .clone()
+        .try_into_dynamic()
+        .map_err(|_| AsepriteError::UnsupportedFormat(atlas_texture.texture_descriptor.format))
+}
+
+/// The `.meta` file loading the output of [`write_processed`] with the processed loader.
+pub fn processed_meta(atlas_texture: &Image, settings: &AsepriteSaverSettings) -> Vec<u8> {
+    let meta = AssetMeta::<ProcessedAsepriteLoader, ()>::new(AssetAction::Load {
+        loader: ProcessedAsepriteLoader::type_path().to_string(),
+        settings: loader_settings(atlas_texture, settings),
+    });
+    AssetMetaDyn::serialize(&meta)
+}
+
+fn loader_settings(atlas_texture: &Image, settings: &AsepriteSaverSettings) -> ImageLoaderSettings {
+    let format = match settings.format {
+        ProcessedFormat::Binary => settings.image_format.bevy_format(),
+        ProcessedFormat::JsonSheet => bevy::prelude::ImageFormat::Png,
+    };
+    ImageLoaderSettings {
+        format: ImageFormatSetting::Format(format),
+        is_srgb: atlas_texture.texture_descriptor.format.is_srgb(),
+        sampler: atlas_texture.sampler.clone(),
+        asset_usage: atlas_texture.asset_usage,
+        texture_format: None,
+        array_layout: None,
     }
 }
 
@@ -146,38 +263,209 @@ impl AssetLoader for ProcessedAsepriteLoader {
         reader
             .read_to_end(&mut buf)
             .await
-            .map_err(|_| AsepriteError::ReadError)?;
+            .map_err(|source| AsepriteError::ReadError {
+                path: load_context.path().clone(),
+                source,
+            })?;
+
+        // json sheets are written by `ProcessedFormat::JsonSheet`
+        if buf.first() == Some(&b'{') {
+            let sheet_settings = AsepriteLoaderSettings {
+                sampler: settings.sampler.clone(),
+                ..default()
+            };
+            return load_sheet(&buf, &sheet_settings, load_context).await;
+        }
+
+        let Some(magic) = buf.get(..MAGIC.len()) else {
+            return Err(AsepriteError::TruncatedProcessedFile);
+        };
+        if magic != MAGIC {
+            return Err(AsepriteError::UnsupportedProcessedFormat);
+        }
 
-        let msgpack_size = u64::from_be_bytes(
-            (&buf[..8])
-                .try_into()
-                .expect("Buffer should be at least 8 bytes long"),
-        );
+        let Some(header) = buf.get(..HEADER_SIZE) else {
+            return Err(AsepriteError::TruncatedProcessedFile);
+        };
 
-        let msgpack = &buf[8..8 + msgpack_size as usize];
+        let version = u16::from_be_bytes([header[4], header[5]]);
+        if version != PROCESSED_FORMAT_VERSION {
+            return Err(AsepriteError::ProcessedVersionMismatch {
+                found: version,
+                expected: PROCESSED_FORMAT_VERSION,
+            });
+        }
+
+        let mut msgpack_size = [0; 8];
+        msgpack_size.copy_from_slice(&header[6..HEADER_SIZE]);
+        let msgpack_end = usize::try_from(u64::from_be_bytes(msgpack_size))
+            .ok()
+            .and_then(|size| HEADER_SIZE.checked_add(size))
+            .ok_or(AsepriteError::TruncatedProcessedFile)?;
+
+        let Some(msgpack) = buf.get(HEADER_SIZE..msgpack_end) else {
+            return Err(AsepriteError::TruncatedProcessedFile);
+        };
 
         let de: AsepriteDeserialize = rmp_serde::from_slice(msgpack)?;
 
-        let atlas_texture_buf = &buf[8 + msgpack_size as usize..];
+        let atlas_texture_buf = &buf[msgpack_end..];
+        if atlas_texture_buf.is_empty() {
+            return Err(AsepriteError::TruncatedProcessedFile);
+        }
+
+        // files without a format in their settings predate format selection
+        let image_format = match settings.format {
+            ImageFormatSetting::Format(format) => format,
+            _ => bevy::prelude::ImageFormat::Qoi,
+        };
 
         let atlas_texture = Image::from_buffer(
             atlas_texture_buf,
-            ImageType::Format(bevy::prelude::ImageFormat::Qoi),
+            ImageType::Format(image_format),
             self.supported_compressed_formats,
             settings.is_srgb,
             settings.sampler.clone(),
             settings.asset_usage,
         )?;
 
+        add_sub_images(
+            "",
+            de.aseprite.sub_images,
+            &de.aseprite.slices,
+            &de.aseprite.frame_indicies,
+            &de.atlas_layout,
+            &atlas_texture,
+            load_context,
+        );
+
         let atlas_layout_handle =
             load_context.add_labeled_asset("atlas_layout".into(), de.atlas_layout);
         let atlas_texture_handle =
             load_context.add_labeled_asset("atlas_texture".into(), atlas_texture);
 
+        let palette_texture = match de.aseprite.palette.is_empty() {
+            true => None,
+            false => Some(
+                load_context
+                    .add_labeled_asset("palette".into(), palette_image(&de.aseprite.palette)),
+            ),
+        };
+
         Ok(Aseprite {
             atlas_layout: atlas_layout_handle,
             atlas_image: atlas_texture_handle,
+            palette_texture,
             ..de.aseprite
         })
     }
 }
+
+/// Writes an uncompressed rgba8 ktx2 file, optionally with a box filtered mip chain.
+fn write_ktx2(image: &image::RgbaImage, is_srgb: bool, generate_mipmaps: bool) -> Vec<u8> {
+    const IDENTIFIER: [u8; 12] = [
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+    const VK_FORMAT_R8G8B8A8_SRGB: u32 = 43;
+    const HEADER_SIZE: usize = 80;
+    const LEVEL_INDEX_SIZE: usize = 24;
+    const DFD_SIZE: usize = 4 + 24 + 16 * 4;
+
+    let mut levels = vec![image.clone()];
+    if generate_mipmaps {
+        while let Some(last) = levels.last() {
+            if last.width() <= 1 && last.height() <= 1 {
+                break;
+            }
+            let next = image::imageops::resize(
+                last,
+                (last.width() / 2).max(1),
+                (last.height() / 2).max(1),
+                image::imageops::FilterType::Triangle,
+            );
+            levels.push(next);
+        }
+    }
+
+    // ----------------------------- data format descriptor
+    let mut dfd = Vec::with_capacity(DFD_SIZE);
+    dfd.extend_from_slice(&(DFD_SIZE as u32).to_le_bytes());
+    // vendor id and descriptor type
+    dfd.extend_from_slice(&0u32.to_le_bytes());
+    // version 2 and block size
+    dfd.extend_from_slice(&(2 | ((DFD_SIZE as u32 - 4) << 16)).to_le_bytes());
+    // rgbsda color model, bt709 primaries, transfer function, straight alpha
+    dfd.extend_from_slice(&[1, 1, if is_srgb { 2 } else { 1 }, 0]);
+    // texel block dimensions
+    dfd.extend_from_slice(&[0, 0, 0, 0]);
+    // bytes per plane
+    dfd.extend_from_slice(&[4, 0, 0, 0, 0, 0, 0, 0]);
+    for (index, channel) in [0u32, 1, 2, 15].into_iter().enumerate() {
+        // alpha is always linear
+        let channel_type = match (channel, is_srgb) {
+            (15, true) => channel | 0x10,
+            _ => channel,
+        };
+        dfd.extend_from_slice(
+            &((index as u32 * 8) | (7 << 16) | (channel_type << 24)).to_le_bytes(),
+        );
+        dfd.extend_from_slice(&[0, 0, 0, 0]);
+        dfd.extend_from_slice(&0u32.to_le_bytes());
+        dfd.extend_from_slice(&255u32.to_le_bytes());
+    }
+
+    // ----------------------------- level layout, smallest level first
+    let dfd_offset = HEADER_SIZE + LEVEL_INDEX_SIZE * levels.len();
+    let mut data_offset = dfd_offset + DFD_SIZE;
+    let mut level_offsets = vec![0; levels.len()];
+    for (index, level) in levels.iter().enumerate().rev() {
+        data_offset = data_offset.next_multiple_of(4);
+        level_offsets[index] = data_offset;
+        data_offset += level.as_raw().len();
+    }
+
+    let mut out = Vec::with_capacity(data_offset);
+    out.extend_from_slice(&IDENTIFIER);
+    let vk_format = if is_srgb {
+        VK_FORMAT_R8G8B8A8_SRGB
+    } else {
+        VK_FORMAT_R8G8B8A8_UNORM
+    };
+    for value in [
+        vk_format,
+        1, // type size
+        image.width(),
+        image.height(),
+        0, // depth
+        0, // layers
+        1, // faces
+        levels.len() as u32,
+        0, // supercompression
+        dfd_offset as u32,
+        DFD_SIZE as u32,
+        0, // key value data offset
+        0, // key value data length
+    ] {
+        out.extend_from_slice(&value.to_le_bytes());
+    }
+    // supercompression global data offset and length
+    out.extend_from_slice(&0u64.to_le_bytes());
+    out.extend_from_slice(&0u64.to_le_bytes());
+
+    for (level, offset) in levels.iter().zip(level_offsets.iter()) {
+        let length = level.as_raw().len() as u64;
+        out.extend_from_slice(&(*offset as u64).to_le_bytes());
+        out.extend_from_slice(&length.to_le_bytes());
+        out.extend_from_slice(&length.to_le_bytes());
+    }
+
+    out.extend_from_slice(&dfd);
+
+    for (index, level) in levels.iter().enumerate().rev() {
+        out.resize(level_offsets[index], 0);
+        out.extend_from_slice(level.as_raw());
+    }
+
+    out
+}
@@ -0,0 +1,257 @@
+use crate::{
+    animation::{render_animation, AnimationState, RenderAnimation},
+    loader::{Aseprite, SliceMeta},
+    slice::{render_slice, RenderSlice},
+};
+use bevy::{
+    asset::embedded_asset,
+    ecs::component::Mutable,
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::{AsBindGroup, ShaderType},
+    shader::{load_shader_library, ShaderRef},
+    sprite_render::{AlphaMode2d, Material2d, Material2dPlugin},
+    ui::UiSystems,
+};
+use std::ops::DerefMut;
+
+/// maximum number of entries in a [`ColorRemap`]
+pub const MAX_REMAP_COLORS: usize = 16;
+
+/// Renders [`ColorRemapMaterial`] animations and slices on 2d meshes and ui nodes.
+///
+/// Not part of [`AsepriteUltraPlugin`](crate::prelude::AsepriteUltraPlugin),
+/// since it requires the render plugins.
+pub struct ColorRemapPlugin;
+
+impl Plugin for ColorRemapPlugin {
+    fn build(&self, app: &mut App) {
+        load_shader_library!(app, "color_remap_common.wgsl");
+        embedded_asset!(app, "color_remap.wgsl");
+        embedded_asset!(app, "color_remap_ui.wgsl");
+        app.add_plugins(Material2dPlugin::<ColorRemapMaterial>::default());
+        app.add_plugins(UiMaterialPlugin::<ColorRemapMaterial>::default());
+        app.add_systems(
+            PostUpdate,
+            (
+                apply_color_remap::<MaterialNode<ColorRemapMaterial>>,
+                render_animation::<MaterialNode<ColorRemapMaterial>>,
+                render_slice::<MaterialNode<ColorRemapMaterial>>,
+            )
+                .before(UiSystems::Prepare),
+        );
+        app.add_systems(
+            PostUpdate,
+            (
+                apply_color_remap::<MeshMaterial2d<ColorRemapMaterial>>,
+                render_animation::<MeshMaterial2d<ColorRemapMaterial>>,
+                render_slice::<MeshMaterial2d<ColorRemapMaterial>>,
+            ),
+        );
+        app.register_type::<ColorRemap>();
+    }
+}
+
+/// Replaces exact colors of an animation or slice rendered with a [`ColorRemapMaterial`].
+///
+/// Colors are compared in srgb with 8 bit precision, alpha included.
+/// Only the first [`MAX_REMAP_COLORS`] entries are used.
+/// An entity sharing its material with others gets a copy of it, so the remap stays its own.
+/// Removing the component restores the original colors.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(
+/// #     mut cmd: Commands,
+/// #     server: Res<AssetServer>,
+/// #     mut meshes: ResMut<Assets<Mesh>>,
+/// #     mut materials: ResMut<Assets<ColorRemapMaterial>>,
+/// # ) {
+/// cmd.spawn((
+///     AseAnimation {
+///         aseprite: server.load("player.aseprite"),
+///         animation: Animation::tag("walk-right"),
+///     },
+///     ColorRemap::default().with(Color::srgb_u8(255, 0, 0), Color::srgb_u8(0, 0, 255)),
+///     Mesh2d(meshes.add(Rectangle::from_size(Vec2::splat(32.0)))),
+///     MeshMaterial2d(materials.add(ColorRemapMaterial::default())),
+/// ));
+/// # }
+/// ```
+#[derive(Component, Reflect, Default, Debug, Clone)]
+#[reflect]
+pub struct ColorRemap {
+    /// source and target color pairs
+    pub colors: Vec<(Color, Color)>,
+}
+
+impl ColorRemap {
+    /// adds a replacement of `source` with `target`
+    pub fn with(mut self, source: impl Into<Color>, target: impl Into<Color>) -> Self {
+        self.colors.push((source.into(), target.into()));
+        self
+    }
+}
+
+#[derive(ShaderType, Debug, Clone, Default)]
+pub struct ColorRemapUniform {
+    /// srgb source colors
+    pub sources: [Vec4; MAX_REMAP_COLORS],
+    /// linear target colors
+    pub targets: [Vec4; MAX_REMAP_COLORS],
+    pub count: u32,
+}
+
+impl From<&ColorRemap> for ColorRemapUniform {
+    fn from(remap: &ColorRemap) -> Self {
+        let mut uniform = ColorRemapUniform::default();
+        if remap.colors.len() > MAX_REMAP_COLORS {
+            warn!("color remap has more than {MAX_REMAP_COLORS} colors, extra colors are ignored");
+        }
+        for (index, (source, target)) in remap.colors.iter().take(MAX_REMAP_COLORS).enumerate() {
+            uniform.sources[index] = source.to_srgba().to_vec4();
+            uniform.targets[index] = target.to_linear().to_vec4();
+            uniform.count += 1;
+        }
+        uniform
+    }
+}
+
+/// Samples the atlas and replaces the colors of the entity [`ColorRemap`]
+#[derive(AsBindGroup, Asset, TypePath, Debug, Clone, Default)]
+pub struct ColorRemapMaterial {
+    #[texture(0)]
+    pub image: Handle<Image>,
+    #[uniform(1)]
+    pub texture_min: UVec2,
+    #[uniform(2)]
+    pub texture_max: UVec2,
+    #[uniform(3)]
+    pub remap: ColorRemapUniform,
+}
+
+impl Material2d for ColorRemapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_aseprite_ultra/color_remap.wgsl".into()
+    }
+    fn alpha_mode(&self) -> AlphaMode2d {
+        AlphaMode2d::Blend
+    }
+}
+
+impl UiMaterial for ColorRemapMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "embedded://bevy_aseprite_ultra/color_remap_ui.wgsl".into()
+    }
+}
+
+impl RenderAnimation for ColorRemapMaterial {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_animation(
+        &mut self,
+        aseprite: &Aseprite,
+        state: &AnimationState,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let Some(atlas_layout) = extra.get(&aseprite.atlas_layout) else {
+            return;
+        };
+        let index = aseprite.get_atlas_index(usize::from(state.current_frame));
+        let Some(rect) = atlas_layout.textures.get(index) else {
+            return;
+        };
+        self.image = aseprite.atlas_image.clone();
+        self.texture_min = rect.min;
+        self.texture_max = rect.max;
+    }
+}
+
+impl RenderSlice for ColorRemapMaterial {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_slice(
+        &mut self,
+        aseprite: &Aseprite,
+        slice_meta: &SliceMeta,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let Some(rect) = extra
+            .get(&aseprite.atlas_layout)
+            .and_then(|layout| layout.textures.get(slice_meta.atlas_id))
+        else {
+            return;
+        };
+        self.image = aseprite.atlas_image.clone();
+        self.texture_min = rect.min;
+        self.texture_max = rect.max;
+    }
+}
+
+fn apply_color_remap<
+    T: Component<Mutability = Mutable> + DerefMut<Target = Handle<ColorRemapMaterial>>,
+>(
+    mut targets: Query<(Entity, Option<Ref<ColorRemap>>, &mut T)>,
+    mut removed: RemovedComponents<ColorRemap>,
+    mut applied: Local<HashMap<Entity, AssetId<ColorRemapMaterial>>>,
+    mut materials: ResMut<Assets<ColorRemapMaterial>>,
+) {
+    // a removed remap clears the material, unless the entity is gone or got a new one
+    let mut pending = Vec::new();
+    for entity in removed.read() {
+        applied.remove(&entity);
+        if let Ok((_, None, _)) = targets.get(entity) {
+            pending.push((entity, None));
+        }
+    }
+
+    // the target is marked changed by the render systems every frame,
+    // so the material it was last applied to is compared instead
+    for (entity, remap, target) in &targets {
+        let Some(remap) = remap else {
+            continue;
+        };
+        if !remap.is_changed() && applied.get(&entity) == Some(&target.id()) {
+            continue;
+        }
+        pending.push((entity, Some(ColorRemapUniform::from(remap.as_ref()))));
+    }
+
+    if pending.is_empty() {
+        return;
+    }
+
+    let mut users = HashMap::<AssetId<ColorRemapMaterial>, usize>::new();
+    for (_, _, target) in &targets {
+        *users.entry(target.id()).or_default() += 1;
+    }
+
+    for (entity, uniform) in pending {
+        let Ok((_, _, mut target)) = targets.get_mut(entity) else {
+            continue;
+        };
+        let Some(material) = materials.get(&**target) else {
+            continue;
+        };
+        if uniform.is_none() && material.remap.count == 0 {
+            continue;
+        }
+
+        // the remap lives in the material, shared materials are copied first
+        let users = users.entry(target.id()).or_default();
+        if *users > 1 {
+            *users -= 1;
+            let material = material.clone();
+            **target = materials.add(material);
+        }
+        if let Some(material) = materials.get_mut(&**target) {
+            match uniform {
+                Some(uniform) => {
+                    material.remap = uniform;
+                    applied.insert(entity, target.id());
+                }
+                None => material.remap = ColorRemapUniform::default(),
+            }
+        }
+    }
+}
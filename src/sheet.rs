@@ -0,0 +1,487 @@
+use crate::{
+    error::AsepriteError,
+    loader::{
+        clamp_tag_range, Aseprite, AsepriteLoaderSettings, AsepriteParts, SliceKeyMeta, SliceParts,
+        TagMeta, MAX_ATLAS_SIZE,
+    },
+};
+use aseprite_loader::binary::chunks::tags::AnimationDirection;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, RenderAssetUsages},
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    platform::collections::HashMap,
+    prelude::*,
+    render::render_resource::TextureFormat,
+};
+use serde::{Deserialize, Deserializer, Serialize};
+
+pub struct AsepriteSheetLoaderPlugin;
+impl Plugin for AsepriteSheetLoaderPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_asset_loader(AsepriteSheetLoader);
+    }
+}
+
+/// Loads the json + png sprite sheet exported by the aseprite cli
+/// (`--sheet player.png --data player.aseprite.json`), in hash or array format.
+///
+/// Only `.aseprite.json` files are claimed, other json stays with other loaders.
+/// The png is resolved relative to the json by `meta.image`,
+/// or decoded from `meta.image` if it is a base64 `data:image/png` uri.
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # fn setup(server: Res<AssetServer>) {
+/// let aseprite: Handle<Aseprite> = server.load("player.aseprite.json");
+/// # }
+/// ```
+#[derive(Default, TypePath)]
+pub struct AsepriteSheetLoader;
+
+#[derive(Serialize, Deserialize)]
+struct Sheet {
+    frames: SheetFrames,
+    meta: SheetMeta,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum SheetFrames {
+    Array(Vec<SheetFrame>),
+    Hash(#[serde(deserialize_with = "ordered_frames")] Vec<SheetFrame>),
+}
+
+/// keeps the document order of the hash format, which is the frame order
+fn ordered_frames<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<SheetFrame>, D::Error> {
+    struct FramesVisitor;
+    impl<'de> serde::de::Visitor<'de> for FramesVisitor {
+        type Value = Vec<SheetFrame>;
+
+        fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+            formatter.write_str("a map of frames")
+        }
+
+        fn visit_map<A: serde::de::MapAccess<'de>>(
+            self,
+            mut map: A,
+        ) -> Result<Self::Value, A::Error> {
+            let mut frames = Vec::new();
+            while let Some((filename, mut frame)) = map.next_entry::<String, SheetFrame>()? {
+                frame.filename = filename;
+                frames.push(frame);
+            }
+            Ok(frames)
+        }
+    }
+    deserializer.deserialize_map(FramesVisitor)
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SheetFrame {
+    #[serde(default)]
+    filename: String,
+    frame: SheetRect,
+    #[serde(default)]
+    rotated: bool,
+    #[serde(default)]
+    trimmed: bool,
+    sprite_source_size: SheetRect,
+    source_size: SheetSize,
+    duration: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SheetMeta {
+    #[serde(default)]
+    app: String,
+    #[serde(default)]
+    version: String,
+    image: String,
+    #[serde(default)]
+    format: String,
+    #[serde(default)]
+    size: Option<SheetSize>,
+    #[serde(default)]
+    scale: String,
+    #[serde(default)]
+    frame_tags: Vec<SheetTag>,
+    #[serde(default)]
+    slices: Vec<SheetSlice>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SheetTag {
+    name: String,
+    from: u16,
+    to: u16,
+    direction: String,
+    /// aseprite writes the repeat count as string
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    repeat: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SheetSlice {
+    name: String,
+    #[serde(default)]
+    color: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data: Option<String>,
+    keys: Vec<SheetSliceKey>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SheetSliceKey {
+    frame: usize,
+    bounds: SheetRect,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    center: Option<SheetRect>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pivot: Option<SheetPoint>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SheetRect {
+    x: i32,
+    y: i32,
+    w: u32,
+    h: u32,
+}
+
+impl From<Rect> for SheetRect {
+    fn from(rect: Rect) -> Self {
+        Self {
+            x: rect.min.x as i32,
+            y: rect.min.y as i32,
+            w: rect.width() as u32,
+            h: rect.height() as u32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SheetSize {
+    w: u32,
+    h: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct SheetPoint {
+    x: i32,
+    y: i32,
+}
+
+const DATA_URI_PNG: &str = "data:image/png;base64,";
+
+impl AssetLoader for AsepriteSheetLoader {
+    type Asset = Aseprite;
+    type Settings = AsepriteLoaderSettings;
+    type Error = AsepriteError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        settings: &Self::Settings,
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader
+            .read_to_end(&mut bytes)
+            .await
+            .map_err(|source| AsepriteError::ReadError {
+                path: load_context.path().clone(),
+                source,
+            })?;
+
+        load_sheet(&bytes, settings, load_context).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["aseprite.json"]
+    }
+}
+
+/// Parses a sprite sheet json and assembles it into an [`Aseprite`]
+pub(crate) async fn load_sheet(
+    bytes: &[u8],
+    settings: &AsepriteLoaderSettings,
+    load_context: &mut LoadContext<'_>,
+) -> Result<Aseprite, AsepriteError> {
+    let sheet: Sheet = serde_json::from_slice(bytes)?;
+
+    // ----------------------------- sheet image
+    let embedded = sheet.meta.image.strip_prefix(DATA_URI_PNG);
+    let image_error = || AsepriteError::SheetImageError {
+        image: match embedded {
+            Some(_) => "embedded png".into(),
+            None => sheet.meta.image.clone(),
+        },
+    };
+    let image_bytes = match embedded {
+        Some(data) => BASE64.decode(data).map_err(|_| image_error())?,
+        None => {
+            let image_path = load_context
+                .path()
+                .resolve_embed(&sheet.meta.image)
+                .map_err(|_| image_error())?;
+            load_context
+                .read_asset_bytes(image_path)
+                .await
+                .map_err(|_| image_error())?
+        }
+    };
+    let sheet_image = Image::from_buffer(
+        &image_bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )?
+    .convert(TextureFormat::Rgba8UnormSrgb)
+    .ok_or_else(image_error)?;
+    let sheet_width = sheet_image.width() as i64;
+    let sheet_height = sheet_image.height() as i64;
+    let sheet_data = sheet_image.data.unwrap_or_default();
+
+    // ----------------------------- frames
+    let frames = match sheet.frames {
+        SheetFrames::Array(frames) => frames,
+        SheetFrames::Hash(frames) => frames,
+    };
+
+    let Some(first) = frames.first() else {
+        return Err(AsepriteError::NoFrames);
+    };
+    let canvas = UVec2::new(first.source_size.w, first.source_size.h);
+    if canvas.min_element() == 0 || canvas.max_element() > MAX_ATLAS_SIZE {
+        return Err(AsepriteError::InvalidCanvasSize {
+            width: canvas.x,
+            height: canvas.y,
+            max: MAX_ATLAS_SIZE,
+        });
+    }
+
+    // frames may be trimmed, restore the full canvas
+    let images = frames
+        .iter()
+        .map(|frame| {
+            let mut buffer = vec![0; canvas.x as usize * canvas.y as usize * 4];
+            // pixels past the canvas are skipped anyway
+            for y in 0..frame.frame.h.min(canvas.y) as i64 {
+                for x in 0..frame.frame.w.min(canvas.x) as i64 {
+                    let (src_x, src_y) = (frame.frame.x as i64 + x, frame.frame.y as i64 + y);
+                    let (dst_x, dst_y) = (
+                        frame.sprite_source_size.x as i64 + x,
+                        frame.sprite_source_size.y as i64 + y,
+                    );
+                    if src_x >= sheet_width
+                        || src_y >= sheet_height
+                        || dst_x >= canvas.x as i64
+                        || dst_y >= canvas.y as i64
+                        || src_x < 0
+                        || src_y < 0
+                        || dst_x < 0
+                        || dst_y < 0
+                    {
+                        continue;
+                    }
+                    let src = ((src_y * sheet_width + src_x) * 4) as usize;
+                    let dst = ((dst_y * canvas.x as i64 + dst_x) * 4) as usize;
+                    if let Some(pixel) = sheet_data.get(src..src + 4) {
+                        buffer[dst..dst + 4].copy_from_slice(pixel);
+                    }
+                }
+            }
+            buffer
+        })
+        .collect();
+
+    let frame_durations = frames
+        .iter()
+        .map(|frame| std::time::Duration::from_millis(frame.duration))
+        .collect();
+
+    // ---------------------------- tags
+    let last_frame = (frames.len() - 1).min(u16::MAX as usize) as u16;
+    let mut tags = HashMap::new();
+    sheet.meta.frame_tags.iter().for_each(|tag| {
+        let direction = match tag.direction.as_str() {
+            "reverse" => AnimationDirection::Reverse,
+            "pingpong" => AnimationDirection::PingPong,
+            "pingpong_reverse" => AnimationDirection::PingPongReverse,
+            _ => AnimationDirection::Forward,
+        };
+        let repeat = match &tag.repeat {
+            Some(serde_json::Value::String(repeat)) => repeat.parse().unwrap_or(0),
+            Some(serde_json::Value::Number(repeat)) => {
+                repeat.as_u64().unwrap_or(0).min(u16::MAX as u64) as u16
+            }
+            _ => 0,
+        };
+        tags.insert(
+            tag.name.clone(),
+            TagMeta {
+                direction,
+                range: clamp_tag_range(&tag.name, tag.from..=tag.to, last_frame),
+                repeat,
+            },
+        );
+    });
+
+    // ----------------------------- slices
+    let slices = sheet
+        .meta
+        .slices
+        .into_iter()
+        .map(|slice| SliceParts {
+            name: slice.name,
+            keys: slice
+                .keys
+                .iter()
+                .map(|key| {
+                    let min = Vec2::new(key.bounds.x as f32, key.bounds.y as f32);
+                    let max = min + Vec2::new(key.bounds.w as f32, key.bounds.h as f32);
+                    SliceKeyMeta {
+                        frame: key.frame,
+                        rect: Rect::from_corners(min, max),
+                        pivot: key.pivot.map(|p| Vec2::new(p.x as f32, p.y as f32)),
+                        nine_patch: key
+                            .center
+                            .map(|c| Vec4::new(c.x as f32, c.y as f32, c.w as f32, c.h as f32)),
+                    }
+                })
+                .collect(),
+            user_data: slice.data,
+        })
+        .collect();
+
+    AsepriteParts {
+        canvas,
+        images,
+        frame_durations,
+        tags,
+        slices,
+        palette: Vec::new(),
+        normal_images: None,
+        emissive_images: None,
+    }
+    .assemble(settings, load_context)
+}
+
+/// Writes an [`Aseprite`] as aseprite sprite sheet json in array format.
+/// `image` ends up in `meta.image`, a path or a png data uri.
+#[cfg(feature = "asset_processing")]
+pub(crate) fn sheet_json(
+    aseprite: &Aseprite,
+    layout: &TextureAtlasLayout,
+    image: String,
+) -> Result<String, AsepriteError> {
+    let frames = aseprite
+        .frame_durations
+        .iter()
+        .enumerate()
+        .map(|(index, duration)| {
+            let rect = layout
+                .textures
+                .get(aseprite.get_atlas_index(index))
+                .cloned()
+                .unwrap_or_default();
+            SheetFrame {
+                filename: index.to_string(),
+                frame: SheetRect::from(rect.as_rect()),
+                rotated: false,
+                trimmed: false,
+                sprite_source_size: SheetRect {
+                    x: 0,
+                    y: 0,
+                    w: rect.width(),
+                    h: rect.height(),
+                },
+                source_size: SheetSize {
+                    w: rect.width(),
+                    h: rect.height(),
+                },
+                duration: duration.as_millis() as u64,
+            }
+        })
+        .collect();
+
+    let mut tags = aseprite.tags.iter().collect::<Vec<_>>();
+    tags.sort_by(|(a_name, a), (b_name, b)| {
+        (a.range.start(), a_name).cmp(&(b.range.start(), b_name))
+    });
+    let frame_tags = tags
+        .into_iter()
+        .map(|(name, tag)| SheetTag {
+            name: name.clone(),
+            from: *tag.range.start(),
+            to: *tag.range.end(),
+            direction: match tag.direction {
+                AnimationDirection::Reverse => "reverse",
+                AnimationDirection::PingPong => "pingpong",
+                AnimationDirection::PingPongReverse => "pingpong_reverse",
+                _ => "forward",
+            }
+            .into(),
+            repeat: (tag.repeat > 0).then(|| serde_json::Value::String(tag.repeat.to_string())),
+        })
+        .collect();
+
+    let mut slices = aseprite.slices.iter().collect::<Vec<_>>();
+    slices.sort_by_key(|(name, _)| *name);
+    let slices = slices
+        .into_iter()
+        .map(|(name, slice)| SheetSlice {
+            name: name.clone(),
+            color: "#0000ffff".into(),
+            data: slice.user_data.clone(),
+            keys: slice
+                .keys
+                .iter()
+                .map(|key| SheetSliceKey {
+                    frame: key.frame,
+                    bounds: SheetRect::from(key.rect),
+                    center: key.nine_patch.map(|np| SheetRect {
+                        x: np.x as i32,
+                        y: np.y as i32,
+                        w: np.z as u32,
+                        h: np.w as u32,
+                    }),
+                    pivot: key.pivot.map(|p| SheetPoint {
+                        x: p.x as i32,
+                        y: p.y as i32,
+                    }),
+                })
+                .collect(),
+        })
+        .collect();
+
+    let sheet = Sheet {
+        frames: SheetFrames::Array(frames),
+        meta: SheetMeta {
+            app: env!("CARGO_PKG_REPOSITORY").into(),
+            version: env!("CARGO_PKG_VERSION").into(),
+            image,
+            format: "RGBA8888".into(),
+            size: Some(SheetSize {
+                w: layout.size.x,
+                h: layout.size.y,
+            }),
+            scale: "1".into(),
+            frame_tags,
+            slices,
+        },
+    };
+
+    Ok(serde_json::to_string_pretty(&sheet)?)
+}
+
+/// Embeds png bytes as `meta.image` data uri
+#[cfg(feature = "asset_processing")]
+pub(crate) fn png_data_uri(png: &[u8]) -> String {
+    format!("{DATA_URI_PNG}{}", BASE64.encode(png))
+}
@@ -1,4 +1,7 @@
-use crate::loader::{Aseprite, SliceMeta};
+use crate::{
+    error::{report, AseError, AsepriteError},
+    loader::{Aseprite, SliceMeta},
+};
 use bevy::{
     ecs::component::Mutable, prelude::*, sprite::Anchor, sprite_render::Material2d, ui::UiSystems,
 };
@@ -18,8 +21,20 @@ impl Plugin for AsepriteSlicePlugin {
 
 /// Anything component that implements this trait is a render target for [`AseSlice`]
 ///
+/// The authored pivot of the slice is available through [`SliceMeta::anchor`],
+/// so custom materials can offset their geometry or uvs accordingly.
+///
 /// # Examples
 /// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # #[derive(Component)]
+/// # struct MyMaterial {
+/// #     image: Handle<Image>,
+/// #     texture_min: UVec2,
+/// #     texture_max: UVec2,
+/// #     time: f32,
+/// # }
 /// impl RenderSlice for MyMaterial {
 ///     type Extra<'e> = Res<'e, Time>;
 ///     fn render_slice(
@@ -68,6 +83,21 @@ impl RenderSlice for Sprite {
     }
 }
 
+impl<M: UiMaterial + RenderSlice> RenderSlice for MaterialNode<M> {
+    type Extra<'e> = (ResMut<'e, Assets<M>>, <M as RenderSlice>::Extra<'e>);
+    fn render_slice(
+        &mut self,
+        aseprite: &Aseprite,
+        slice_meta: &SliceMeta,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let Some(material) = extra.0.get_mut(&*self) else {
+            return;
+        };
+        material.render_slice(aseprite, slice_meta, &mut extra.1);
+    }
+}
+
 impl<M: Material2d + RenderSlice> RenderSlice for MeshMaterial2d<M> {
     type Extra<'e> = (ResMut<'e, Assets<M>>, <M as RenderSlice>::Extra<'e>);
     fn render_slice(
@@ -107,27 +137,61 @@ pub struct AseSlice {
     pub aseprite: Handle<Aseprite>,
 }
 
+/// Offsets a ui node, so its pivot lands where the node would be centered.
+pub(crate) fn ui_pivot_translation(anchor: &Anchor) -> Val2 {
+    Val2::percent(-anchor.x * 100.0, anchor.y * 100.0)
+}
+
+/// a slice render target, with the components its pivot moves
+type SliceTarget<'a, T> = (
+    Entity,
+    &'a mut T,
+    Ref<'a, AseSlice>,
+    Option<&'a mut Anchor>,
+    Option<&'a mut UiTransform>,
+    Option<&'a AseError>,
+);
+
 pub fn render_slice<T: RenderSlice + Component<Mutability = Mutable>>(
-    mut slices: Query<(&mut T, Ref<AseSlice>, Option<&mut Anchor>)>,
+    mut cmd: Commands,
+    mut slices: Query<SliceTarget<T>>,
     aseprites: Res<Assets<Aseprite>>,
     mut extra: <T as RenderSlice>::Extra<'_>,
 ) {
     let asset_change = aseprites.is_changed();
 
-    for (mut target, slice, maybe_anchor) in &mut slices {
+    for (entity, mut target, slice, maybe_anchor, maybe_ui_transform, error) in &mut slices {
         if !asset_change && !slice.is_changed() {
             continue;
         }
         let Some(aseprite) = aseprites.get(&slice.aseprite) else {
             continue;
         };
-        let Some(slice_meta) = aseprite.slices.get(&slice.name) else {
-            warn!("slice does not exist {}", slice.name);
+        let slice_meta = aseprite.slices.get(&slice.name);
+        report(
+            &mut cmd,
+            entity,
+            error,
+            slice_meta.is_none().then(|| AsepriteError::MissingSlice {
+                slice: slice.name.clone(),
+            }),
+        );
+        let Some(slice_meta) = slice_meta else {
             continue;
         };
 
+        let slice_anchor = slice_meta.anchor();
+
         if let Some(mut anchor) = maybe_anchor {
-            *anchor = Anchor::from(slice_meta);
+            anchor.set_if_neq(slice_anchor);
+        }
+
+        // a translation set by hand is left alone on slices without a pivot
+        if let (Some(mut ui_transform), Some(_)) = (maybe_ui_transform, slice_meta.pivot) {
+            let translation = ui_pivot_translation(&slice_anchor);
+            if ui_transform.translation != translation {
+                ui_transform.translation = translation;
+            }
         }
 
         target.render_slice(aseprite, slice_meta, &mut extra);
@@ -0,0 +1,167 @@
+use crate::{animation::AnimationState, AsepriteUltraPlugin};
+use bevy::{
+    asset::LoadState,
+    image::{ImagePlugin, TextureAtlasPlugin},
+    prelude::*,
+    time::TimeUpdateStrategy,
+};
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+/// how long the helpers wait for the asset server or processor
+const LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A headless app with [`AsepriteUltraPlugin`], no window or gpu required.
+/// Time only moves with [`advance`].
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::{prelude::*, testing::*};
+/// # use std::time::Duration;
+/// let mut app = test_app();
+/// let aseprite = app
+///     .world_mut()
+///     .resource_mut::<Assets<Aseprite>>()
+///     .add(AsepriteBuilder::new().frames(4, Duration::from_millis(100)).build());
+/// let entity = app
+///     .world_mut()
+///     .spawn(AseAnimation { aseprite, ..default() })
+///     .id();
+/// advance(&mut app, Duration::ZERO);
+/// advance(&mut app, Duration::from_millis(100));
+/// assert_eq!(current_frame(&app, entity), 1);
+/// ```
+pub fn test_app() -> App {
+    let mut app = headless_app(AssetPlugin::default());
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    app.world_mut()
+        .resource_mut::<Time<Virtual>>()
+        .set_max_delta(Duration::from_secs(3600));
+    // the first update only starts the clock
+    app.update();
+    app
+}
+
+/// Runs one update of the app, advancing time by exactly `duration`.
+pub fn advance(app: &mut App, duration: Duration) {
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(duration));
+    app.update();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+}
+
+/// The current frame of an animated entity.
+pub fn current_frame(app: &App, entity: Entity) -> u16 {
+    app.world()
+        .get::<AnimationState>(entity)
+        .map(|state| state.current_frame)
+        .unwrap_or_default()
+}
+
+/// A headless app with [`AsepriteUltraPlugin`] and the given [`AssetPlugin`],
+/// for loading or processing files. Time runs as usual.
+pub fn headless_app(assets: AssetPlugin) -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        assets,
+        ImagePlugin::default(),
+        TextureAtlasPlugin,
+        AsepriteUltraPlugin,
+    ));
+    app
+}
+
+/// A [`headless_app`] loading assets from `dir`.
+///
+/// # Examples
+/// ```no_run
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::{prelude::*, testing::*};
+/// let mut app = asset_app(std::path::Path::new("assets"));
+/// let player = load::<Aseprite>(&mut app, "player.aseprite");
+/// ```
+pub fn asset_app(dir: &Path) -> App {
+    headless_app(AssetPlugin {
+        file_path: dir.to_string_lossy().into(),
+        ..default()
+    })
+}
+
+/// An empty directory in the system temp dir, unique to `name` and this process.
+pub fn output_dir(name: &str) -> PathBuf {
+    let dir =
+        std::env::temp_dir().join(format!("bevy_aseprite_ultra_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("temp dir is writable");
+    dir
+}
+
+/// Updates the app until every handle is loaded or failed, then returns their load states.
+/// Handles still loading after 30 seconds are returned as they are.
+pub fn load_states<A: Asset>(app: &mut App, handles: &[Handle<A>]) -> Vec<LoadState> {
+    let started = Instant::now();
+    loop {
+        app.update();
+        let server = app.world().resource::<AssetServer>();
+        let states = handles
+            .iter()
+            .map(|handle| {
+                server
+                    .get_load_state(handle)
+                    .unwrap_or(LoadState::NotLoaded)
+            })
+            .collect::<Vec<_>>();
+        let done = states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded | LoadState::Failed(_)));
+        if done || started.elapsed() > LOAD_TIMEOUT {
+            return states;
+        }
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Loads every path, panicking if one fails or does not finish.
+pub fn load_all<A: Asset>(app: &mut App, paths: &[impl AsRef<str>]) -> Vec<Handle<A>> {
+    let paths = paths.iter().map(AsRef::as_ref).collect::<Vec<_>>();
+    let handles = paths
+        .iter()
+        .map(|path| app.world().resource::<AssetServer>().load(path.to_string()))
+        .collect::<Vec<_>>();
+    for (path, state) in paths.iter().zip(load_states(app, &handles)) {
+        match state {
+            LoadState::Loaded => {}
+            LoadState::Failed(err) => panic!("failed to load {path}: {err}"),
+            _ => panic!("{path} did not finish loading"),
+        }
+    }
+    handles
+}
+
+/// Loads `path`, panicking if it fails or does not finish.
+pub fn load<A: Asset>(app: &mut App, path: &str) -> Handle<A> {
+    load_all(app, &[path]).remove(0)
+}
+
+/// Updates the app until the asset processor finished, panicking after 30 seconds.
+#[cfg(feature = "asset_processing")]
+pub fn finish_processing(app: &mut App) {
+    use bevy::asset::processor::{AssetProcessor, ProcessorState};
+
+    let started = Instant::now();
+    loop {
+        app.update();
+        let processor = app.world().resource::<AssetProcessor>();
+        if bevy::tasks::block_on(processor.get_state()) == ProcessorState::Finished {
+            return;
+        }
+        assert!(
+            started.elapsed() < LOAD_TIMEOUT,
+            "asset processing did not finish"
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    animation::{render_animation, AnimationState, RenderAnimation},
+    error::AsepriteError,
+    loader::{crop, Aseprite, SliceMeta},
+    slice::{render_slice, RenderSlice},
+};
+use bevy::{
+    ecs::{entity::EntityHashSet, system::NonSendMarker},
+    prelude::*,
+    window::{CursorIcon, CustomCursor, CustomCursorImage},
+    winit::WINIT_WINDOWS,
+};
+
+/// Renders [`AseAnimation`](crate::prelude::AseAnimation) and [`AseSlice`](crate::prelude::AseSlice)
+/// onto the window [`CursorIcon`] and sets the [`AseWindowIcon`].
+pub struct AsepriteWindowPlugin;
+
+impl Plugin for AsepriteWindowPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            PostUpdate,
+            (
+                render_animation::<CursorIcon>,
+                render_slice::<CursorIcon>,
+                update_window_icon,
+            ),
+        );
+        app.register_type::<AseWindowIcon>();
+    }
+}
+
+/// pixel hotspot of a normalized anchor inside `size`
+fn hotspot(anchor: Vec2, size: UVec2) -> (u16, u16) {
+    let pixel = (Vec2::new(anchor.x + 0.5, 0.5 - anchor.y) * size.as_vec2())
+        .round()
+        .min(size.saturating_sub(UVec2::ONE).as_vec2())
+        .max(Vec2::ZERO);
+    (pixel.x as u16, pixel.y as u16)
+}
+
+/// Add an [`AseSlice`](crate::prelude::AseSlice) or [`AseAnimation`](crate::prelude::AseAnimation)
+/// to the window entity, next to its [`CursorIcon`].
+/// The slice pivot, or the frame anchor of animations, is the hotspot.
+/// Without a pivot, the hotspot is the top left corner.
+impl RenderSlice for CursorIcon {
+    type Extra<'e> = ();
+    fn render_slice(&mut self, aseprite: &Aseprite, slice_meta: &SliceMeta, _extra: &mut ()) {
+        let size = slice_meta.rect.size().as_uvec2();
+        let pivot = slice_meta
+            .pivot
+            .unwrap_or_default()
+            .as_uvec2()
+            .min(size.saturating_sub(UVec2::ONE));
+        *self = CursorIcon::Custom(CustomCursor::Image(CustomCursorImage {
+            handle: aseprite.atlas_image.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: aseprite.atlas_layout.clone(),
+                index: slice_meta.atlas_id,
+            }),
+            hotspot: (pivot.x as u16, pivot.y as u16),
+            ..default()
+        }));
+    }
+}
+
+impl RenderAnimation for CursorIcon {
+    type Extra<'e> = Res<'e, Assets<TextureAtlasLayout>>;
+    fn render_animation(
+        &mut self,
+        aseprite: &Aseprite,
+        state: &AnimationState,
+        extra: &mut Self::Extra<'_>,
+    ) {
+        let frame = usize::from(state.current_frame);
+        let index = aseprite.get_atlas_index(frame);
+        let Some(rect) = extra
+            .get(&aseprite.atlas_layout)
+            .and_then(|layout| layout.textures.get(index))
+        else {
+            return;
+        };
+        *self = CursorIcon::Custom(CustomCursor::Image(CustomCursorImage {
+            handle: aseprite.atlas_image.clone(),
+            texture_atlas: Some(TextureAtlas {
+                layout: aseprite.atlas_layout.clone(),
+                index,
+            }),
+            hotspot: aseprite
+                .get_frame_anchor(frame)
+                .map(|anchor| hotspot(anchor.0, rect.size()))
+                .unwrap_or_default(),
+            ..default()
+        }));
+    }
+}
+
+/// Sets the window icon from a slice, add it to the window entity.
+/// The icon is set again when the file is reloaded.
+///
+/// Only slices are supported, for an icon from an animation frame
+/// add a slice around it.
+///
+/// # Examples
+/// ```
+/// # use bevy::{prelude::*, window::PrimaryWindow};
+/// # use bevy_aseprite_ultra::prelude::*;
+/// fn setup(mut cmd: Commands, window: Single<Entity, With<PrimaryWindow>>, server: Res<AssetServer>) {
+///     cmd.entity(*window).insert(AseWindowIcon {
+///         slice: "icon".into(),
+///         aseprite: server.load("ui.aseprite"),
+///     });
+/// }
+/// ```
+#[derive(Component, Reflect, Default, Debug, Clone)]
+pub struct AseWindowIcon {
+    pub slice: String,
+    pub aseprite: Handle<Aseprite>,
+}
+
+fn update_window_icon(
+    icons: Query<(Entity, Ref<AseWindowIcon>)>,
+    mut events: MessageReader<AssetEvent<Aseprite>>,
+    aseprites: Res<Assets<Aseprite>>,
+    images: Res<Assets<Image>>,
+    layouts: Res<Assets<TextureAtlasLayout>>,
+    mut applied: Local<EntityHashSet>,
+    _main_thread: NonSendMarker,
+) {
+    let modified = events
+        .read()
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, icon) in &icons {
+        if icon.is_changed() || modified.contains(&icon.aseprite.id()) {
+            applied.remove(&entity);
+        }
+        if applied.contains(&entity) {
+            continue;
+        }
+
+        let Some(aseprite) = aseprites.get(&icon.aseprite) else {
+            continue;
+        };
+        let Some(slice) = aseprite.slices.get(&icon.slice) else {
+            let error = AsepriteError::MissingSlice {
+                slice: icon.slice.clone(),
+            };
+            warn!("{entity}: {error}");
+            applied.insert(entity);
+            continue;
+        };
+        let (Some(atlas), Some(layout)) = (
+            images.get(&aseprite.atlas_image),
+            layouts.get(&aseprite.atlas_layout),
+        ) else {
+            continue;
+        };
+        let Some(rect) = layout.textures.get(slice.atlas_id) else {
+            continue;
+        };
+
+        let image = crop(atlas, *rect);
+        let (width, height) = (image.width(), image.height());
+        let Some(rgba) = image.data else {
+            continue;
+        };
+        let icon = match winit::window::Icon::from_rgba(rgba, width, height) {
+            Ok(icon) => icon,
+            Err(err) => {
+                error!("invalid window icon: {err}");
+                applied.insert(entity);
+                continue;
+            }
+        };
+
+        let set = WINIT_WINDOWS.with_borrow(|windows| {
+            let Some(window) = windows.get_window(entity) else {
+                return false;
+            };
+            window.set_window_icon(Some(icon));
+            true
+        });
+        if set {
+            applied.insert(entity);
+        }
+    }
+}
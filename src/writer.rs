@@ -0,0 +1,367 @@
+use crate::{
+    animation::AnimationDirection,
+    error::AsepriteError,
+    loader::{crop, Aseprite, AsepriteParts, SliceParts, TagMeta, MAX_ATLAS_SIZE},
+};
+use bevy::{platform::collections::HashMap, prelude::*, render::render_resource::TextureFormat};
+use std::time::Duration;
+
+const HEADER_MAGIC: u16 = 0xA5E0;
+const FRAME_MAGIC: u16 = 0xF1FA;
+const CHUNK_LAYER: u16 = 0x2004;
+const CHUNK_CEL: u16 = 0x2005;
+const CHUNK_TAGS: u16 = 0x2018;
+const CHUNK_PALETTE: u16 = 0x2019;
+const CHUNK_USER_DATA: u16 = 0x2020;
+const CHUNK_SLICE: u16 = 0x2022;
+const CEL_COMPRESSED_IMAGE: u16 = 2;
+
+/// Serializes an [`Aseprite`] into a .aseprite file with a single layer,
+/// keeping tags, slices, durations and the palette.
+/// The frames are read from its atlas, which has to be loaded.
+///
+/// # Examples
+/// ```
+/// # use bevy::prelude::*;
+/// # use bevy_aseprite_ultra::prelude::*;
+/// # #[derive(Resource)]
+/// # struct Variation(Handle<Aseprite>);
+/// fn save(
+///     aseprites: Res<Assets<Aseprite>>,
+///     images: Res<Assets<Image>>,
+///     layouts: Res<Assets<TextureAtlasLayout>>,
+///     variation: Res<Variation>,
+/// ) {
+///     let aseprite = aseprites.get(&variation.0).unwrap();
+///     let bytes = write_aseprite(aseprite, &images, &layouts).unwrap();
+///     std::fs::write("assets/variation.aseprite", bytes).unwrap();
+/// }
+/// ```
+pub fn write_aseprite(
+    aseprite: &Aseprite,
+    images: &Assets<Image>,
+    layouts: &Assets<TextureAtlasLayout>,
+) -> Result<Vec<u8>, AsepriteError> {
+    let (Some(atlas), Some(layout)) = (
+        images.get(&aseprite.atlas_image),
+        layouts.get(&aseprite.atlas_layout),
+    ) else {
+        return Err(AsepriteError::AtlasNotLoaded);
+    };
+
+    let frames = (0..aseprite.frame_durations.len())
+        .map(|frame| {
+            let rect = layout
+                .textures
+                .get(aseprite.get_atlas_index(frame))
+                .copied()
+                .unwrap_or_default();
+            crop(atlas, rect)
+        })
+        .collect::<Vec<_>>();
+
+    let mut slices = aseprite
+        .slices
+        .iter()
+        .map(|(name, slice)| SliceParts {
+            name: name.clone(),
+            keys: slice.keys.clone(),
+            user_data: slice.user_data.clone(),
+        })
+        .collect::<Vec<_>>();
+    slices.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let parts = frame_parts(
+        frames.iter().map(Some),
+        aseprite.frame_durations.clone(),
+        aseprite.tags.clone(),
+        slices,
+        aseprite.palette.clone(),
+    )?;
+    encode(&parts)
+}
+
+/// converts frame images into canvas sized rgba8 buffers, missing frames stay transparent
+pub(crate) fn frame_parts<'a>(
+    frames: impl Iterator<Item = Option<&'a Image>>,
+    frame_durations: Vec<Duration>,
+    tags: HashMap<String, TagMeta>,
+    slices: Vec<SliceParts>,
+    palette: Vec<Color>,
+) -> Result<AsepriteParts, AsepriteError> {
+    let frames = frames
+        .map(|frame| {
+            frame
+                .map(|image| match image.texture_descriptor.format {
+                    TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm => Ok(image.clone()),
+                    format => image
+                        .convert(TextureFormat::Rgba8UnormSrgb)
+                        .ok_or(AsepriteError::UnsupportedFormat(format)),
+                })
+                .transpose()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let canvas = frames
+        .iter()
+        .flatten()
+        .map(Image::size)
+        .fold(UVec2::ONE, UVec2::max);
+    // larger canvases would not load again
+    if canvas.max_element() > MAX_ATLAS_SIZE {
+        return Err(AsepriteError::InvalidCanvasSize {
+            width: canvas.x,
+            height: canvas.y,
+            max: MAX_ATLAS_SIZE,
+        });
+    }
+
+    // frames smaller than the canvas are placed at its top left
+    let images = frames
+        .iter()
+        .map(|frame| {
+            let mut buffer = vec![0; canvas.x as usize * canvas.y as usize * 4];
+            let Some(image) = frame else {
+                return buffer;
+            };
+            let data = image.data.as_deref().unwrap_or_default();
+            let row = image.width() as usize * 4;
+            for (y, source) in data.chunks_exact(row.max(1)).enumerate() {
+                let start = y * canvas.x as usize * 4;
+                buffer[start..start + row].copy_from_slice(source);
+            }
+            buffer
+        })
+        .collect();
+
+    Ok(AsepriteParts {
+        canvas,
+        images,
+        frame_durations,
+        tags,
+        slices,
+        palette,
+        normal_images: None,
+        emissive_images: None,
+    })
+}
+
+/// writes the parts as .aseprite binary, with one layer and one cel per frame
+pub(crate) fn encode(parts: &AsepriteParts) -> Result<Vec<u8>, AsepriteError> {
+    let frame_count = u16::try_from(parts.images.len())
+        .map_err(|_| AsepriteError::TooManyFrames(parts.images.len()))?;
+
+    let mut file = Bytes::default();
+    // ---------------------------- header
+    file.dword(0); // file size, patched below
+    file.word(HEADER_MAGIC);
+    file.word(frame_count);
+    file.word(parts.canvas.x as u16);
+    file.word(parts.canvas.y as u16);
+    file.word(32); // rgba
+    file.dword(1); // layer opacity is valid
+    file.word(100); // deprecated speed
+    file.dword(0);
+    file.dword(0);
+    file.byte(0); // transparent palette index
+    file.zeros(3);
+    file.word(parts.palette.len().min(u16::MAX as usize) as u16);
+    file.byte(1); // pixel ratio
+    file.byte(1);
+    file.short(0); // grid
+    file.short(0);
+    file.word(16);
+    file.word(16);
+    file.zeros(84);
+
+    // ---------------------------- frames
+    for (index, image) in parts.images.iter().enumerate() {
+        let mut chunks = Vec::new();
+
+        if index == 0 {
+            if !parts.palette.is_empty() {
+                chunks.push(palette_chunk(&parts.palette));
+            }
+            chunks.push(layer_chunk("Layer 1"));
+        }
+
+        chunks.push(cel_chunk(parts.canvas, image));
+
+        if index == 0 {
+            if !parts.tags.is_empty() {
+                chunks.push(tags_chunk(parts));
+            }
+            for slice in parts.slices.iter() {
+                chunks.push(slice_chunk(slice));
+                if let Some(text) = slice.user_data.as_ref() {
+                    chunks.push(user_data_chunk(text));
+                }
+            }
+        }
+
+        let duration = parts
+            .frame_durations
+            .get(index)
+            .map(|duration| duration.as_millis().min(u16::MAX as u128) as u16)
+            .unwrap_or(100);
+
+        let mut frame = Bytes::default();
+        frame.dword(16 + chunks.iter().map(|chunk| chunk.len() as u32).sum::<u32>());
+        frame.word(FRAME_MAGIC);
+        frame.word(chunks.len().min(0xFFFF) as u16);
+        frame.word(duration);
+        frame.zeros(2);
+        frame.dword(chunks.len() as u32);
+        for chunk in chunks {
+            frame.0.extend(chunk);
+        }
+        file.0.extend(frame.0);
+    }
+
+    let size = file.0.len() as u32;
+    file.0[0..4].copy_from_slice(&size.to_le_bytes());
+    Ok(file.0)
+}
+
+fn palette_chunk(palette: &[Color]) -> Vec<u8> {
+    let mut data = Bytes::default();
+    data.dword(palette.len() as u32);
+    data.dword(0);
+    data.dword(palette.len().saturating_sub(1) as u32);
+    data.zeros(8);
+    for color in palette {
+        data.word(0); // no name
+        data.0.extend(color.to_srgba().to_u8_array());
+    }
+    chunk(CHUNK_PALETTE, data)
+}
+
+fn layer_chunk(name: &str) -> Vec<u8> {
+    let mut data = Bytes::default();
+    data.word(1 | 2); // visible and editable
+    data.word(0); // normal layer
+    data.word(0); // child level
+    data.word(0);
+    data.word(0);
+    data.word(0); // normal blend mode
+    data.byte(255);
+    data.zeros(3);
+    data.string(name);
+    chunk(CHUNK_LAYER, data)
+}
+
+fn cel_chunk(canvas: UVec2, image: &[u8]) -> Vec<u8> {
+    let mut data = Bytes::default();
+    data.word(0); // layer
+    data.short(0);
+    data.short(0);
+    data.byte(255);
+    data.word(CEL_COMPRESSED_IMAGE);
+    data.short(0); // z index
+    data.zeros(5);
+    data.word(canvas.x as u16);
+    data.word(canvas.y as u16);
+    data.0.extend(miniz_oxide::deflate::compress_to_vec_zlib(image, 6));
+    chunk(CHUNK_CEL, data)
+}
+
+fn tags_chunk(parts: &AsepriteParts) -> Vec<u8> {
+    let mut tags = parts.tags.iter().collect::<Vec<_>>();
+    tags.sort_by(|(a_name, a), (b_name, b)| {
+        (a.range.start(), a_name).cmp(&(b.range.start(), b_name))
+    });
+
+    let mut data = Bytes::default();
+    data.word(tags.len() as u16);
+    data.zeros(8);
+    for (name, tag) in tags {
+        data.word(*tag.range.start());
+        data.word(*tag.range.end());
+        data.byte(match AnimationDirection::from(tag.direction) {
+            AnimationDirection::Forward => 0,
+            AnimationDirection::Reverse => 1,
+            AnimationDirection::PingPong => 2,
+            AnimationDirection::PingPongReverse => 3,
+        });
+        data.word(tag.repeat);
+        data.zeros(6);
+        data.zeros(4); // deprecated color
+        data.string(name);
+    }
+    chunk(CHUNK_TAGS, data)
+}
+
+fn slice_chunk(slice: &SliceParts) -> Vec<u8> {
+    let nine_patch = slice.keys.iter().any(|key| key.nine_patch.is_some());
+    let pivot = slice.keys.iter().any(|key| key.pivot.is_some());
+
+    let mut data = Bytes::default();
+    data.dword(slice.keys.len() as u32);
+    data.dword((nine_patch as u32) | ((pivot as u32) << 1));
+    data.dword(0);
+    data.string(&slice.name);
+    for key in slice.keys.iter() {
+        data.dword(key.frame as u32);
+        data.long(key.rect.min.x as i32);
+        data.long(key.rect.min.y as i32);
+        data.dword(key.rect.width() as u32);
+        data.dword(key.rect.height() as u32);
+        if nine_patch {
+            let center = key.nine_patch.unwrap_or_default();
+            data.long(center.x as i32);
+            data.long(center.y as i32);
+            data.dword(center.z as u32);
+            data.dword(center.w as u32);
+        }
+        if pivot {
+            let pivot = key.pivot.unwrap_or_default();
+            data.long(pivot.x as i32);
+            data.long(pivot.y as i32);
+        }
+    }
+    chunk(CHUNK_SLICE, data)
+}
+
+fn user_data_chunk(text: &str) -> Vec<u8> {
+    let mut data = Bytes::default();
+    data.dword(1); // has text
+    data.string(text);
+    chunk(CHUNK_USER_DATA, data)
+}
+
+fn chunk(kind: u16, data: Bytes) -> Vec<u8> {
+    let mut chunk = Bytes::default();
+    chunk.dword(6 + data.0.len() as u32);
+    chunk.word(kind);
+    chunk.0.extend(data.0);
+    chunk.0
+}
+
+/// little endian writer for the aseprite primitive types
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Bytes {
+    fn byte(&mut self, value: u8) {
+        self.0.push(value);
+    }
+    fn word(&mut self, value: u16) {
+        self.0.extend(value.to_le_bytes());
+    }
+    fn short(&mut self, value: i16) {
+        self.0.extend(value.to_le_bytes());
+    }
+    fn dword(&mut self, value: u32) {
+        self.0.extend(value.to_le_bytes());
+    }
+    fn long(&mut self, value: i32) {
+        self.0.extend(value.to_le_bytes());
+    }
+    fn zeros(&mut self, count: usize) {
+        self.0.extend(std::iter::repeat_n(0, count));
+    }
+    fn string(&mut self, value: &str) {
+        self.word(value.len() as u16);
+        self.0.extend(value.as_bytes());
+    }
+}
@@ -0,0 +1,175 @@
+use bevy::prelude::*;
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+const FRAME: Duration = Duration::from_millis(100);
+
+/// eight frames, with a tag over frames 2 to 5 for every direction
+fn aseprite() -> Aseprite {
+    AsepriteBuilder::new()
+        .frames(8, FRAME)
+        .tag(
+            "forward",
+            2..=5,
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "reverse",
+            2..=5,
+            AnimationDirection::Reverse,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "pingpong",
+            2..=5,
+            AnimationDirection::PingPong,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "pingpong_reverse",
+            2..=5,
+            AnimationDirection::PingPongReverse,
+            AnimationRepeat::Loop,
+        )
+        .build()
+}
+
+/// the frames shown after spawning and after each of `steps` frame durations
+fn play(aseprite: Aseprite, animation: Animation, steps: usize) -> (App, Entity, Vec<u16>) {
+    let mut app = test_app();
+    let aseprite = app
+        .world_mut()
+        .resource_mut::<Assets<Aseprite>>()
+        .add(aseprite);
+    let entity = app
+        .world_mut()
+        .spawn(AseAnimation {
+            aseprite,
+            animation,
+        })
+        .id();
+
+    advance(&mut app, Duration::ZERO);
+    let mut frames = vec![current_frame(&app, entity)];
+    for _ in 0..steps {
+        advance(&mut app, FRAME);
+        frames.push(current_frame(&app, entity));
+    }
+    (app, entity, frames)
+}
+
+fn frames(animation: Animation, steps: usize) -> Vec<u16> {
+    play(aseprite(), animation, steps).2
+}
+
+fn finished(app: &mut App, entity: Entity) -> bool {
+    app.world_mut()
+        .resource_mut::<Messages<AnimationEvents>>()
+        .drain()
+        .any(|event| matches!(event, AnimationEvents::Finished(e) if e == entity))
+}
+
+#[test]
+fn forward_loop() {
+    assert_eq!(
+        frames(Animation::tag("forward"), 9),
+        [2, 3, 4, 5, 2, 3, 4, 5, 2, 3]
+    );
+}
+
+#[test]
+fn forward_count() {
+    let animation = Animation::tag("forward").with_repeat(AnimationRepeat::Count(1));
+    let (mut app, entity, frames) = play(aseprite(), animation, 9);
+    assert_eq!(frames, [2, 3, 4, 5, 2, 3, 4, 5, 5, 5]);
+    assert!(finished(&mut app, entity));
+}
+
+#[test]
+fn reverse_loop() {
+    assert_eq!(
+        frames(Animation::tag("reverse"), 9),
+        [5, 4, 3, 2, 5, 4, 3, 2, 5, 4]
+    );
+}
+
+#[test]
+fn reverse_count() {
+    let animation = Animation::tag("reverse").with_repeat(AnimationRepeat::Count(0));
+    let (mut app, entity, frames) = play(aseprite(), animation, 5);
+    assert_eq!(frames, [5, 4, 3, 2, 2, 2]);
+    assert!(finished(&mut app, entity));
+}
+
+#[test]
+fn pingpong_loop() {
+    assert_eq!(
+        frames(Animation::tag("pingpong"), 12),
+        [2, 3, 4, 5, 4, 3, 2, 3, 4, 5, 4, 3, 2]
+    );
+}
+
+#[test]
+fn pingpong_count() {
+    let animation = Animation::tag("pingpong").with_repeat(AnimationRepeat::Count(1));
+    let (mut app, entity, frames) = play(aseprite(), animation, 8);
+    assert_eq!(frames, [2, 3, 4, 5, 4, 3, 2, 2, 2]);
+    assert!(finished(&mut app, entity));
+}
+
+#[test]
+fn pingpong_reverse_loop() {
+    assert_eq!(
+        frames(Animation::tag("pingpong_reverse"), 12),
+        [5, 4, 3, 2, 3, 4, 5, 4, 3, 2, 3, 4, 5]
+    );
+}
+
+#[test]
+fn pingpong_reverse_count() {
+    let animation = Animation::tag("pingpong_reverse").with_repeat(AnimationRepeat::Count(0));
+    let (mut app, entity, frames) = play(aseprite(), animation, 5);
+    assert_eq!(frames, [5, 4, 3, 2, 2, 2]);
+    assert!(finished(&mut app, entity));
+}
+
+#[test]
+fn direction_override() {
+    let animation = Animation::tag("forward").with_direction(AnimationDirection::Reverse);
+    assert_eq!(frames(animation, 5), [5, 4, 3, 2, 5, 4]);
+}
+
+#[test]
+fn whole_file_without_tag() {
+    assert_eq!(
+        frames(Animation::default(), 9),
+        [0, 1, 2, 3, 4, 5, 6, 7, 0, 1]
+    );
+}
+
+#[test]
+fn frames_last_their_duration() {
+    let aseprite = AsepriteBuilder::new()
+        .frame(FRAME)
+        .frame(FRAME * 3)
+        .frame(FRAME * 2)
+        .build();
+    let (_, _, frames) = play(aseprite, Animation::default(), 7);
+    assert_eq!(frames, [0, 1, 1, 1, 2, 2, 0, 1]);
+}
+
+#[test]
+fn speed_scales_durations() {
+    assert_eq!(
+        frames(Animation::tag("forward").with_speed(0.5), 4),
+        [2, 2, 3, 3, 4]
+    );
+}
+
+#[test]
+fn missing_tag_marks_entity() {
+    let (app, entity, frames) = play(aseprite(), Animation::tag("missing"), 2);
+    assert_eq!(frames, [0, 1, 2]);
+    assert!(app.world().get::<AseError>(entity).is_some());
+}
@@ -0,0 +1,255 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+/// a frame filled with one color and a marker pixel at `marker`
+fn frame(size: UVec2, color: [u8; 4], marker: UVec2) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &color,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    image
+        .pixel_bytes_mut(marker.extend(0))
+        .unwrap()
+        .copy_from_slice(&[255, 255, 255, 128]);
+    image
+}
+
+fn frame_pixels(aseprite: &Aseprite, frame: usize, app: &App) -> Vec<u8> {
+    let images = app.world().resource::<Assets<Image>>();
+    let layouts = app.world().resource::<Assets<TextureAtlasLayout>>();
+    let atlas = images.get(&aseprite.atlas_image).unwrap();
+    let rect =
+        layouts.get(&aseprite.atlas_layout).unwrap().textures[aseprite.get_atlas_index(frame)];
+    let mut pixels = Vec::new();
+    for y in rect.min.y..rect.max.y {
+        for x in rect.min.x..rect.max.x {
+            pixels.extend(atlas.pixel_bytes(UVec3::new(x, y, 0)).unwrap());
+        }
+    }
+    pixels
+}
+
+fn assert_same(expected: &Aseprite, actual: &Aseprite) {
+    assert_eq!(expected.frame_durations, actual.frame_durations);
+
+    assert_eq!(expected.tags.len(), actual.tags.len());
+    for (name, tag) in expected.tags.iter() {
+        let loaded = actual.tags.get(name).expect("tag is written");
+        assert_eq!(tag.range, loaded.range, "{name} range");
+        assert_eq!(tag.repeat, loaded.repeat, "{name} repeat");
+        assert_eq!(
+            format!("{:?}", tag.direction),
+            format!("{:?}", loaded.direction),
+            "{name} direction"
+        );
+    }
+
+    assert_eq!(expected.slices.len(), actual.slices.len());
+    for (name, slice) in expected.slices.iter() {
+        let loaded = actual.slices.get(name).expect("slice is written");
+        assert_eq!(slice.rect, loaded.rect, "{name} rect");
+        assert_eq!(slice.pivot, loaded.pivot, "{name} pivot");
+        assert_eq!(slice.nine_patch, loaded.nine_patch, "{name} nine patch");
+        assert_eq!(slice.user_data, loaded.user_data, "{name} user data");
+        assert_eq!(slice.keys.len(), loaded.keys.len(), "{name} keys");
+        for (key, loaded) in slice.keys.iter().zip(loaded.keys.iter()) {
+            assert_eq!(key.frame, loaded.frame, "{name} key frame");
+            assert_eq!(key.rect, loaded.rect, "{name} key rect");
+            assert_eq!(key.pivot, loaded.pivot, "{name} key pivot");
+        }
+    }
+}
+
+#[test]
+fn builder_round_trip() {
+    let out = output_dir("writer_builder");
+
+    let size = UVec2::new(12, 8);
+    let builder = AsepriteBuilder::new()
+        .frame_image(
+            frame(size, [255, 0, 0, 255], UVec2::new(1, 1)),
+            Duration::from_millis(50),
+        )
+        .frame_image(
+            frame(size, [0, 255, 0, 64], UVec2::new(10, 6)),
+            Duration::from_millis(120),
+        )
+        .frame(Duration::from_millis(200))
+        .tag(
+            "run",
+            0..=1,
+            AnimationDirection::PingPong,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "idle",
+            2..=2,
+            AnimationDirection::Reverse,
+            AnimationRepeat::Count(3),
+        )
+        // clamped to the frames by both the builder and the loader
+        .tag(
+            "inverted",
+            std::ops::RangeInclusive::new(2, 0),
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "past",
+            1..=9,
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        )
+        .slice(
+            "hit",
+            Rect::new(2.0, 1.0, 10.0, 7.0),
+            Some(Vec2::new(4.0, 3.0)),
+        )
+        .slice_on(
+            "hit",
+            2,
+            Rect::new(1.0, 1.0, 5.0, 5.0),
+            Some(Vec2::new(2.0, 2.0)),
+        )
+        .slice("body", Rect::new(0.0, 0.0, 6.0, 8.0), None);
+    std::fs::write(out.join("built.aseprite"), builder.write().unwrap()).unwrap();
+
+    let mut app = asset_app(&out);
+    let handle = load::<Aseprite>(&mut app, "built.aseprite");
+
+    let expected = builder.build();
+    assert_eq!(expected.tags.get("inverted").unwrap().range, 0..=0);
+    assert_eq!(expected.tags.get("past").unwrap().range, 1..=2);
+    assert_eq!(expected.slices.get("hit").unwrap().keys.len(), 2);
+    let aseprites = app.world().resource::<Assets<Aseprite>>();
+    let loaded = aseprites.get(&handle).unwrap();
+    assert_same(&expected, loaded);
+
+    let first = frame(size, [255, 0, 0, 255], UVec2::new(1, 1));
+    let second = frame(size, [0, 255, 0, 64], UVec2::new(10, 6));
+    assert_eq!(frame_pixels(loaded, 0, &app), *first.data.as_ref().unwrap());
+    assert_eq!(
+        frame_pixels(loaded, 1, &app),
+        *second.data.as_ref().unwrap()
+    );
+    assert!(frame_pixels(loaded, 2, &app).iter().all(|byte| *byte == 0));
+
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn loaded_files_round_trip() {
+    let out = output_dir("writer_loaded");
+
+    let mut app = asset_app(&std::fs::canonicalize("assets").unwrap());
+    let mut written = asset_app(&out);
+    for asset in ["player.aseprite", "ghost_slices.aseprite"] {
+        let source = load::<Aseprite>(&mut app, asset);
+        let bytes = {
+            let world = app.world();
+            write_aseprite(
+                world.resource::<Assets<Aseprite>>().get(&source).unwrap(),
+                world.resource::<Assets<Image>>(),
+                world.resource::<Assets<TextureAtlasLayout>>(),
+            )
+            .unwrap()
+        };
+        std::fs::write(out.join(asset), bytes).unwrap();
+
+        let handle = load::<Aseprite>(&mut written, asset);
+        let expected = app
+            .world()
+            .resource::<Assets<Aseprite>>()
+            .get(&source)
+            .unwrap();
+        let loaded = written
+            .world()
+            .resource::<Assets<Aseprite>>()
+            .get(&handle)
+            .unwrap();
+
+        assert_same(expected, loaded);
+        assert_eq!(expected.palette, loaded.palette, "{asset} palette");
+        for frame in 0..expected.frame_durations.len() {
+            assert_eq!(
+                frame_pixels(expected, frame, &app),
+                frame_pixels(loaded, frame, &written),
+                "{asset} frame {frame}"
+            );
+        }
+    }
+
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn palette_round_trip() {
+    let out = output_dir("writer_palette");
+
+    let palette = vec![
+        Color::srgba_u8(0, 0, 0, 0),
+        Color::srgb_u8(0, 0, 0),
+        Color::srgb_u8(200, 40, 10),
+        Color::srgba_u8(10, 20, 30, 128),
+    ];
+    let mut app = asset_app(&out);
+    let bytes = app
+        .world_mut()
+        .resource_scope(|world, mut images: Mut<Assets<Image>>| {
+            let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+            let mut aseprite = AsepriteBuilder::new()
+                .frame_image(
+                    frame(UVec2::new(4, 4), [200, 40, 10, 255], UVec2::ZERO),
+                    Duration::from_millis(100),
+                )
+                .build_atlas(&mut images, &mut layouts)
+                .unwrap();
+            aseprite.palette = palette.clone();
+            write_aseprite(&aseprite, &images, &layouts).unwrap()
+        });
+    std::fs::write(out.join("palette.aseprite"), bytes).unwrap();
+
+    let handle = load::<Aseprite>(&mut app, "palette.aseprite");
+    let loaded = app
+        .world()
+        .resource::<Assets<Aseprite>>()
+        .get(&handle)
+        .unwrap();
+    assert_eq!(loaded.palette, palette);
+    assert!(loaded.palette_texture.is_some());
+
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn canvases_the_loader_rejects_are_not_written() {
+    let result = AsepriteBuilder::new()
+        .frame_image(
+            frame(UVec2::new(4097, 1), [0, 0, 0, 255], UVec2::ZERO),
+            Duration::from_millis(100),
+        )
+        .write();
+    assert!(
+        matches!(
+            result,
+            Err(AsepriteError::InvalidCanvasSize {
+                width: 4097,
+                height: 1,
+                ..
+            })
+        ),
+        "{result:?}"
+    );
+}
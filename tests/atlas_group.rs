@@ -0,0 +1,103 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+fn fill(size: UVec2, pixel: &[u8], format: TextureFormat) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: size.x,
+            height: size.y,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixel,
+        format,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// a grouped file with one frame of `color`, and an index texture of `index` if given
+fn member(app: &mut App, size: u32, color: [u8; 4], index: Option<u8>) -> Handle<Aseprite> {
+    let world = app.world_mut();
+    let mut aseprite = world.resource_scope(|world, mut images: Mut<Assets<Image>>| {
+        let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+        AsepriteBuilder::new()
+            .frame_image(
+                fill(UVec2::splat(size), &color, TextureFormat::Rgba8UnormSrgb),
+                Duration::from_millis(100),
+            )
+            .slice("body", Rect::new(1.0, 1.0, 2.0, 2.0), None)
+            .build_atlas(&mut images, &mut layouts)
+            .unwrap()
+    });
+    let mut images = world.resource_mut::<Assets<Image>>();
+    let atlas_size = images.get(&aseprite.atlas_image).unwrap().size();
+    aseprite.index_texture =
+        index.map(|index| images.add(fill(atlas_size, &[index], TextureFormat::R8Unorm)));
+    aseprite.atlas_group = Some("group".into());
+    world.resource_mut::<Assets<Aseprite>>().add(aseprite)
+}
+
+/// every pixel of `rect` in `image`
+fn pixels(image: &Image, rect: URect) -> Vec<Vec<u8>> {
+    let pixel = image
+        .texture_descriptor
+        .format
+        .block_copy_size(None)
+        .unwrap() as usize;
+    let stride = image.width() as usize * pixel;
+    let data = image.data.as_deref().unwrap();
+    (rect.min.y..rect.max.y)
+        .flat_map(|y| (rect.min.x..rect.max.x).map(move |x| (x as usize, y as usize)))
+        .map(|(x, y)| data[y * stride + x * pixel..][..pixel].to_vec())
+        .collect()
+}
+
+#[test]
+fn group_members_share_every_atlas() {
+    let mut app = test_app();
+    let red = member(&mut app, 4, [255, 0, 0, 255], Some(1));
+    let blue = member(&mut app, 3, [0, 0, 255, 255], None);
+    let originals = [&red, &blue].map(|handle| {
+        let aseprites = app.world().resource::<Assets<Aseprite>>();
+        aseprites.get(handle).unwrap().index_texture.clone()
+    });
+    for _ in 0..3 {
+        advance(&mut app, Duration::ZERO);
+    }
+
+    let world = app.world();
+    let aseprites = world.resource::<Assets<Aseprite>>();
+    let images = world.resource::<Assets<Image>>();
+    let layouts = world.resource::<Assets<TextureAtlasLayout>>();
+    let [red, blue] = [&red, &blue].map(|handle| aseprites.get(handle).unwrap());
+
+    assert_eq!(red.atlas_image, blue.atlas_image);
+    assert_eq!(red.atlas_layout, blue.atlas_layout);
+    let atlas = images.get(&red.atlas_image).unwrap();
+    let layout = layouts.get(&red.atlas_layout).unwrap();
+
+    // only members with an index texture get the grouped one
+    let index_texture = red.index_texture.as_ref().unwrap();
+    assert_ne!(Some(index_texture), originals[0].as_ref());
+    assert!(blue.index_texture.is_none());
+    let index = images.get(index_texture).unwrap();
+    assert_eq!(index.size(), atlas.size());
+
+    for (aseprite, color, index_value) in [(red, [255, 0, 0, 255], 1), (blue, [0, 0, 255, 255], 0)]
+    {
+        let frame = layout.textures[aseprite.get_atlas_index(0)];
+        assert!(pixels(atlas, frame).iter().all(|pixel| *pixel == color));
+        assert!(pixels(index, frame)
+            .iter()
+            .all(|pixel| *pixel == [index_value]));
+
+        let slice = layout.textures[aseprite.slices["body"].atlas_id];
+        assert_eq!(slice.min, frame.min + UVec2::ONE);
+        assert_eq!(slice.size(), UVec2::ONE);
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::{
+    asset::{LoadState, RenderAssetUsages},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+fn frame(width: u32, height: u32) -> Image {
+    Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+/// the glyph rect relative to the frame it was cut from
+fn glyph_rect(app: &App, font: &BitmapFont, char: char, frame: URect) -> URect {
+    let layout = app
+        .world()
+        .resource::<Assets<TextureAtlasLayout>>()
+        .get(&font.atlas_layout)
+        .unwrap();
+    let rect = layout.textures[font.glyphs[&char].atlas_id];
+    URect::from_corners(rect.min - frame.min, rect.max - frame.min)
+}
+
+fn frame_rect(app: &App, font: &BitmapFont) -> URect {
+    let layout = app
+        .world()
+        .resource::<Assets<TextureAtlasLayout>>()
+        .get(&font.atlas_layout)
+        .unwrap();
+    layout.textures[0]
+}
+
+#[test]
+fn fonts_from_slices_and_grids() {
+    let out = output_dir("bitmap_font");
+    let slices = AsepriteBuilder::new()
+        .frame_image(frame(16, 8), Duration::from_millis(100))
+        .slice("glyph_A", Rect::new(0.0, 0.0, 5.0, 7.0), None)
+        .slice("glyph_V", Rect::new(5.0, 0.0, 11.0, 8.0), None)
+        .slice("glyph_AB", Rect::new(11.0, 0.0, 16.0, 8.0), None)
+        .slice("icon", Rect::new(11.0, 0.0, 16.0, 8.0), None)
+        .slice_user_data("glyph_V", "A:-1, V: -2,AB:3,broken,.:x")
+        .write()
+        .unwrap();
+    std::fs::write(out.join("slices.aseprite"), slices).unwrap();
+    let grid = AsepriteBuilder::new()
+        .frame_image(frame(8, 4), Duration::from_millis(100))
+        .write()
+        .unwrap();
+    std::fs::write(out.join("grid.aseprite"), grid).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let handles = [
+        server.load_with_settings::<BitmapFont, _>(
+            "slices.aseprite#font",
+            |settings: &mut AsepriteLoaderSettings| {
+                settings.font = Some(BitmapFontSettings::slices("glyph_"));
+            },
+        ),
+        server.load_with_settings::<BitmapFont, _>(
+            "grid.aseprite#font",
+            |settings: &mut AsepriteLoaderSettings| {
+                let mut font = BitmapFontSettings::grid("ABCD", 4, 2);
+                font.letter_spacing = 1.0;
+                settings.font = Some(font);
+            },
+        ),
+    ];
+    let states = load_states(&mut app, &handles);
+    assert!(
+        states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded)),
+        "{states:?}"
+    );
+
+    let fonts = app.world().resource::<Assets<BitmapFont>>();
+    let [slices, grid] = handles.map(|handle| fonts.get(&handle).unwrap());
+
+    // only single char slices with the prefix become glyphs
+    assert_eq!(slices.glyphs.len(), 2);
+    let frame = frame_rect(&app, slices);
+    assert_eq!(glyph_rect(&app, slices, 'A', frame), URect::new(0, 0, 5, 7));
+    assert_eq!(
+        glyph_rect(&app, slices, 'V', frame),
+        URect::new(5, 0, 11, 8)
+    );
+    assert_eq!(slices.glyphs[&'A'].advance, 5.0);
+    assert_eq!(slices.line_height, 8.0);
+    assert_eq!(slices.space_advance, 6.0);
+
+    // malformed kerning pairs are skipped
+    let kerning = &slices.glyphs[&'V'].kerning;
+    assert_eq!(kerning.len(), 2);
+    assert_eq!(slices.advance('V', Some('A')), 5.0);
+    assert_eq!(slices.advance('V', Some('V')), 4.0);
+    assert_eq!(slices.advance('V', Some('B')), 6.0);
+    assert_eq!(slices.advance('A', Some('V')), 5.0);
+    assert_eq!(slices.advance(' ', None), 6.0);
+
+    // grid cells are filled row by row
+    let frame = frame_rect(&app, grid);
+    for (char, rect) in [
+        ('A', URect::new(0, 0, 4, 2)),
+        ('B', URect::new(4, 0, 8, 2)),
+        ('C', URect::new(0, 2, 4, 4)),
+        ('D', URect::new(4, 2, 8, 4)),
+    ] {
+        assert_eq!(glyph_rect(&app, grid, char, frame), rect, "{char}");
+    }
+    assert_eq!(grid.line_height, 2.0);
+    assert_eq!(grid.advance('A', Some('B')), 5.0);
+
+    let _ = std::fs::remove_dir_all(&out);
+}
@@ -0,0 +1,303 @@
+#!/usr/bin/env python3
+"""Writes the blend mode fixtures of tests/blend_modes.rs.
+
+Each `<mode>.aseprite` has a backdrop layer, a layer with the blend mode at
+opacity 192 and a hidden layer on top. `<mode>.png` is its first frame,
+composited with a port of aseprite's src/doc/blend_funcs.cpp that is kept
+independent of the crate and of aseprite-loader.
+
+    python3 tests/blend/generate.py
+"""
+
+import math
+import os
+import struct
+import zlib
+
+WIDTH, HEIGHT = 16, 8
+OPACITY = 192
+MODES = [
+    "normal", "multiply", "screen", "overlay", "darken", "lighten",
+    "color_dodge", "color_burn", "hard_light", "soft_light", "difference",
+    "exclusion", "hue", "saturation", "color", "luminosity", "addition",
+    "subtract", "divide",
+]
+
+
+# ------------------------------------------------------------ pixels
+
+def pixels(seed, alphas):
+    """deterministic colors, with the alphas cycled through"""
+    state = seed
+    out = []
+    for index in range(WIDTH * HEIGHT):
+        rgb = []
+        for _ in range(3):
+            state = (state * 1103515245 + 12345) & 0x7FFFFFFF
+            rgb.append((state >> 16) & 0xFF)
+        out.append((*rgb, alphas[index % len(alphas)]))
+    return out
+
+
+BACKDROP = pixels(7, [255, 255, 255, 128, 0, 255, 64])
+SOURCE = pixels(11, [255, 200, 0, 255, 96, 255])
+HIDDEN = [(255, 0, 0, 255)] * (WIDTH * HEIGHT)
+# the blended cel is offset and clipped by the canvas
+SOURCE_ORIGIN = (3, 2)
+
+
+# ------------------------------------------------------------ aseprite blenders
+
+def mul8(a, b):
+    t = a * b + 0x80
+    return ((t >> 8) + t) >> 8
+
+
+def div8(a, b):
+    return (a * 0xFF + b // 2) // b
+
+
+def cdiv(a, b):
+    """c integer division, truncating towards zero"""
+    q = abs(a) // abs(b)
+    return q if (a >= 0) == (b >= 0) else -q
+
+
+def normal(back, src, opacity):
+    if back[3] == 0:
+        return (src[0], src[1], src[2], mul8(src[3], opacity))
+    if src[3] == 0:
+        return back
+    sa = mul8(src[3], opacity)
+    ra = sa + back[3] - mul8(back[3], sa)
+    return tuple(back[i] + cdiv((src[i] - back[i]) * sa, ra) for i in range(3)) + (ra,)
+
+
+def merge(back, src, opacity):
+    if back[3] == 0:
+        rgb = src[:3]
+    elif src[3] == 0:
+        rgb = back[:3]
+    else:
+        rgb = tuple(back[i] + mul8(src[i] - back[i], opacity) for i in range(3))
+    alpha = back[3] + mul8(src[3] - back[3], opacity)
+    if alpha == 0:
+        return (0, 0, 0, 0)
+    return tuple(rgb) + (alpha,)
+
+
+def with_alpha(blend):
+    """blends into opaque backdrop pixels, composites normally over transparent ones"""
+    def blender(back, src, opacity):
+        if back[3] == 0:
+            return normal(back, src, opacity)
+        norm = normal(back, src, opacity)
+        blended = blend(back, src, opacity)
+        merged = merge(norm, blended, back[3])
+        composite_alpha = mul8(back[3], mul8(src[3], opacity))
+        return merge(merged, blended, composite_alpha)
+    return blender
+
+
+def channels(f):
+    def blend(back, src, opacity):
+        rgb = tuple(f(back[i], src[i]) for i in range(3))
+        return normal(back, rgb + (src[3],), opacity)
+    return with_alpha(blend)
+
+
+def multiply(b, s):
+    return mul8(b, s)
+
+
+def screen(b, s):
+    return b + s - mul8(b, s)
+
+
+def hard_light(b, s):
+    return multiply(b, s << 1) if s < 128 else screen(b, (s << 1) - 255)
+
+
+def color_dodge(b, s):
+    if b == 0:
+        return 0
+    s = 255 - s
+    return 255 if b >= s else div8(b, s)
+
+
+def color_burn(b, s):
+    if b == 255:
+        return 255
+    b = 255 - b
+    return 0 if b >= s else 255 - div8(b, s)
+
+
+def soft_light(b, s):
+    b, s = b / 255.0, s / 255.0
+    d = ((16 * b - 12) * b + 4) * b if b <= 0.25 else math.sqrt(b)
+    if s <= 0.5:
+        r = b - (1.0 - 2.0 * s) * b * (1.0 - b)
+    else:
+        r = b + (2.0 * s - 1.0) * (d - b)
+    return int(r * 255 + 0.5)
+
+
+def divide(b, s):
+    if b == 0:
+        return 0
+    return 255 if b >= s else div8(b, s)
+
+
+def lum(c):
+    return 0.3 * c[0] + 0.59 * c[1] + 0.11 * c[2]
+
+
+def sat(c):
+    return max(c) - min(c)
+
+
+def clip_color(c):
+    l = lum(c)
+    n, x = min(c), max(c)
+    if n < 0.0:
+        c = [l + (v - l) * l / (l - n) for v in c]
+    if x > 1.0:
+        c = [l + (v - l) * (1.0 - l) / (x - l) for v in c]
+    return c
+
+
+def set_lum(c, l):
+    d = l - lum(c)
+    return clip_color([v + d for v in c])
+
+
+def set_sat(c, s):
+    r, g, b = c
+    # the order aseprite picks, which differs from a stable sort on ties
+    lo = 0 if r < min(g, b) else (1 if g < b else 2)
+    hi = 0 if r > max(g, b) else (1 if g > b else 2)
+    if r > g:
+        mid = 1 if g > b else (2 if r > b else 0)
+    else:
+        mid = (2 if b > r else 0) if g > b else 1
+    c = list(c)
+    if c[hi] > c[lo]:
+        c[mid] = (c[mid] - c[lo]) * s / (c[hi] - c[lo])
+        c[hi] = s
+    else:
+        c[mid] = c[hi] = 0.0
+    c[lo] = 0.0
+    return c
+
+
+def hsl(f):
+    def blend(back, src, opacity):
+        b = [v / 255.0 for v in back[:3]]
+        s = [v / 255.0 for v in src[:3]]
+        rgb = tuple(int(255.0 * v) for v in f(b, s))
+        return normal(back, rgb + (src[3],), opacity)
+    return with_alpha(blend)
+
+
+BLENDERS = {
+    "normal": normal,
+    "multiply": channels(multiply),
+    "screen": channels(screen),
+    "overlay": channels(lambda b, s: hard_light(s, b)),
+    "darken": channels(min),
+    "lighten": channels(max),
+    "color_dodge": channels(color_dodge),
+    "color_burn": channels(color_burn),
+    "hard_light": channels(hard_light),
+    "soft_light": channels(soft_light),
+    "difference": channels(lambda b, s: abs(b - s)),
+    "exclusion": channels(lambda b, s: b + s - 2 * mul8(b, s)),
+    "hue": hsl(lambda b, s: set_lum(set_sat(s, sat(b)), lum(b))),
+    "saturation": hsl(lambda b, s: set_lum(set_sat(b, sat(s)), lum(b))),
+    "color": hsl(lambda b, s: set_lum(s, lum(b))),
+    "luminosity": hsl(lambda b, s: set_lum(b, lum(s))),
+    "addition": channels(lambda b, s: min(b + s, 255)),
+    "subtract": channels(lambda b, s: max(b - s, 0)),
+    "divide": channels(divide),
+}
+
+
+def composite(mode):
+    canvas = [(0, 0, 0, 0)] * (WIDTH * HEIGHT)
+    canvas = [normal(back, src, 255) for back, src in zip(canvas, BACKDROP)]
+    blender = BLENDERS[mode]
+    ox, oy = SOURCE_ORIGIN
+    for y in range(HEIGHT - oy):
+        for x in range(WIDTH - ox):
+            index = (y + oy) * WIDTH + x + ox
+            canvas[index] = blender(canvas[index], SOURCE[y * WIDTH + x], OPACITY)
+    return canvas
+
+
+# ------------------------------------------------------------ aseprite file
+
+def string(text):
+    data = text.encode()
+    return struct.pack("<H", len(data)) + data
+
+
+def chunk(kind, data):
+    return struct.pack("<IH", len(data) + 6, kind) + data
+
+
+def layer(name, visible, mode, opacity):
+    flags = 1 if visible else 0
+    data = struct.pack("<HHHHHHB3x", flags, 0, 0, 0, 0, mode, opacity)
+    return chunk(0x2004, data + string(name))
+
+
+def cel(index, origin, pixels):
+    data = struct.pack("<HhhBHh5x", index, origin[0], origin[1], 255, 2, 0)
+    data += struct.pack("<HH", WIDTH, HEIGHT)
+    data += zlib.compress(bytes(v for pixel in pixels for v in pixel))
+    return chunk(0x2005, data)
+
+
+def aseprite(mode):
+    chunks = [
+        layer("backdrop", True, 0, 255),
+        layer("blend", True, MODES.index(mode), OPACITY),
+        layer("hidden", False, 0, 255),
+        cel(0, (0, 0), BACKDROP),
+        cel(1, SOURCE_ORIGIN, SOURCE),
+        cel(2, (0, 0), HIDDEN),
+    ]
+    body = b"".join(chunks)
+    frame = struct.pack("<IHHH2xI", len(body) + 16, 0xF1FA, len(chunks), 100, len(chunks)) + body
+    header = struct.pack(
+        "<IHHHHHIHIIB3xHBBhhHH84x",
+        128 + len(frame), 0xA5E0, 1, WIDTH, HEIGHT, 32, 1, 100, 0, 0, 0, 0, 1, 1, 0, 0, 16, 16,
+    )
+    return header + frame
+
+
+# ------------------------------------------------------------ png
+
+def png(pixels):
+    def png_chunk(kind, data):
+        return struct.pack(">I", len(data)) + kind + data + struct.pack(">I", zlib.crc32(kind + data))
+
+    rows = b"".join(
+        b"\x00" + bytes(v for pixel in pixels[y * WIDTH:(y + 1) * WIDTH] for v in pixel)
+        for y in range(HEIGHT)
+    )
+    return (
+        b"\x89PNG\r\n\x1a\n"
+        + png_chunk(b"IHDR", struct.pack(">IIBBBBB", WIDTH, HEIGHT, 8, 6, 0, 0, 0))
+        + png_chunk(b"IDAT", zlib.compress(rows))
+        + png_chunk(b"IEND", b"")
+    )
+
+
+if __name__ == "__main__":
+    out = os.path.dirname(os.path.abspath(__file__))
+    for mode in MODES:
+        with open(os.path.join(out, f"{mode}.aseprite"), "wb") as file:
+            file.write(aseprite(mode))
+        with open(os.path.join(out, f"{mode}.png"), "wb") as file:
+            file.write(png(composite(mode)))
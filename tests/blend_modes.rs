@@ -0,0 +1,99 @@
+use bevy::{
+    asset::RenderAssetUsages,
+    image::{CompressedImageFormats, ImageSampler, ImageType},
+    prelude::*,
+    render::render_resource::TextureFormat,
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::path::Path;
+
+const FIXTURES: &str = "tests/blend";
+
+/// one fixture per aseprite blend mode, written by `tests/blend/generate.py`
+const FIXTURE_NAMES: [&str; 19] = [
+    "normal",
+    "multiply",
+    "screen",
+    "overlay",
+    "darken",
+    "lighten",
+    "color_dodge",
+    "color_burn",
+    "hard_light",
+    "soft_light",
+    "difference",
+    "exclusion",
+    "hue",
+    "saturation",
+    "color",
+    "luminosity",
+    "addition",
+    "subtract",
+    "divide",
+];
+
+/// the fixture files, failing if one of them or its reference png is missing
+fn fixtures() -> Vec<String> {
+    FIXTURE_NAMES
+        .iter()
+        .map(|name| {
+            for extension in ["aseprite", "png"] {
+                let path = Path::new(FIXTURES).join(format!("{name}.{extension}"));
+                assert!(path.exists(), "missing blend fixture {}", path.display());
+            }
+            format!("{name}.aseprite")
+        })
+        .collect()
+}
+
+fn reference(name: &str) -> Image {
+    let bytes = std::fs::read(Path::new(FIXTURES).join(format!("{name}.png"))).unwrap();
+    Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::default(),
+    )
+    .unwrap()
+    .convert(TextureFormat::Rgba8UnormSrgb)
+    .unwrap()
+}
+
+#[test]
+fn blend_modes_match_reference_port() {
+    let mut app = asset_app(Path::new(FIXTURES));
+    let handles = load_all::<Aseprite>(&mut app, &fixtures());
+
+    let aseprites = app.world().resource::<Assets<Aseprite>>();
+    let images = app.world().resource::<Assets<Image>>();
+    let layouts = app.world().resource::<Assets<TextureAtlasLayout>>();
+
+    for (name, handle) in FIXTURE_NAMES.iter().zip(handles.iter()) {
+        let aseprite = aseprites.get(handle).expect("fixture is loaded");
+        let atlas = images.get(&aseprite.atlas_image).unwrap();
+        let rect =
+            layouts.get(&aseprite.atlas_layout).unwrap().textures[aseprite.get_atlas_index(0)];
+        let reference = reference(name);
+
+        assert_eq!(reference.size(), rect.size(), "{name} canvas size");
+
+        for y in 0..rect.height() {
+            for x in 0..rect.width() {
+                let expected = reference.pixel_bytes(UVec3::new(x, y, 0)).unwrap();
+                let actual = atlas
+                    .pixel_bytes(UVec3::new(rect.min.x + x, rect.min.y + y, 0))
+                    .unwrap();
+                let matches = expected
+                    .iter()
+                    .zip(actual.iter())
+                    .all(|(e, a)| e.abs_diff(*a) <= 1);
+                assert!(
+                    matches,
+                    "{name} differs at {x},{y}: expected {expected:?}, got {actual:?}"
+                );
+            }
+        }
+    }
+}
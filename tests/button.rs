@@ -0,0 +1,108 @@
+use bevy::{prelude::*, ui::InteractionDisabled};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+/// normal, hover and disabled states as slices and tags, pressed is missing
+fn aseprite() -> Aseprite {
+    let rect = Rect::new(0.0, 0.0, 4.0, 4.0);
+    let mut builder = AsepriteBuilder::new().frames(3, Duration::from_millis(100));
+    for (frame, state) in ["normal", "hover", "disabled"].into_iter().enumerate() {
+        let frame = frame as u16;
+        builder = builder.slice(format!("button_{state}"), rect, None).tag(
+            format!("button_{state}"),
+            frame..=frame,
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        );
+    }
+    builder.build()
+}
+
+fn slice(app: &App, entity: Entity) -> Option<String> {
+    app.world()
+        .get::<AseSlice>(entity)
+        .map(|slice| slice.name.clone())
+}
+
+fn tag(app: &App, entity: Entity) -> Option<String> {
+    app.world()
+        .get::<AseAnimation>(entity)
+        .and_then(|animation| animation.animation.tag.clone())
+}
+
+#[test]
+fn button_states_follow_the_interaction() {
+    let mut app = test_app();
+    let aseprite = app
+        .world_mut()
+        .resource_mut::<Assets<Aseprite>>()
+        .add(aseprite());
+    let entity = app
+        .world_mut()
+        .spawn(AseButton {
+            name: "button".into(),
+            aseprite,
+            ..default()
+        })
+        .id();
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_normal"));
+
+    let set = |app: &mut App, interaction| {
+        *app.world_mut().get_mut::<Interaction>(entity).unwrap() = interaction;
+        advance(app, Duration::ZERO);
+    };
+    set(&mut app, Interaction::Hovered);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_hover"));
+
+    // missing states fall back to normal
+    set(&mut app, Interaction::Pressed);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_normal"));
+
+    set(&mut app, Interaction::Hovered);
+    app.world_mut()
+        .entity_mut(entity)
+        .insert(InteractionDisabled);
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_disabled"));
+
+    app.world_mut()
+        .entity_mut(entity)
+        .remove::<InteractionDisabled>();
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_hover"));
+}
+
+#[test]
+fn changing_the_mode_replaces_the_look() {
+    let mut app = test_app();
+    let aseprite = app
+        .world_mut()
+        .resource_mut::<Assets<Aseprite>>()
+        .add(aseprite());
+    let entity = app
+        .world_mut()
+        .spawn((
+            AseButton {
+                name: "button".into(),
+                aseprite,
+                mode: AseButtonMode::Tag,
+            },
+            Interaction::Hovered,
+        ))
+        .id();
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(tag(&app, entity).as_deref(), Some("button_hover"));
+    assert_eq!(slice(&app, entity), None);
+
+    app.world_mut().get_mut::<AseButton>(entity).unwrap().mode = AseButtonMode::Slice;
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(slice(&app, entity).as_deref(), Some("button_hover"));
+    assert!(app.world().get::<AseAnimation>(entity).is_none());
+    assert!(app.world().get::<AnimationState>(entity).is_none());
+
+    app.world_mut().get_mut::<AseButton>(entity).unwrap().mode = AseButtonMode::Tag;
+    advance(&mut app, Duration::ZERO);
+    assert_eq!(tag(&app, entity).as_deref(), Some("button_hover"));
+    assert_eq!(slice(&app, entity), None);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "debug")]
+
+use bevy::{
+    gizmos::GizmoPlugin,
+    image::{ImagePlugin, TextureAtlasPlugin},
+    prelude::*,
+    transform::TransformPlugin,
+};
+use bevy_aseprite_ultra::prelude::*;
+use std::time::Duration;
+
+fn labels(app: &mut App) -> Vec<(String, Vec3, bool)> {
+    app.world_mut()
+        .query::<(&Text2d, &GlobalTransform, Has<ChildOf>)>()
+        .iter(app.world())
+        .map(|(text, transform, child)| (text.0.clone(), transform.translation(), child))
+        .collect()
+}
+
+#[test]
+fn labels_follow_their_animation_outside_its_hierarchy() {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        ImagePlugin::default(),
+        TextureAtlasPlugin,
+        TransformPlugin,
+        GizmoPlugin,
+        AsepriteUltraPlugin,
+    ));
+    app.finish();
+
+    let aseprite = app
+        .world_mut()
+        .resource_scope(|world, mut images: Mut<Assets<Image>>| {
+            let mut layouts = world.resource_mut::<Assets<TextureAtlasLayout>>();
+            AsepriteBuilder::new()
+                .frames(2, Duration::from_secs(60))
+                .frame_image(Image::default(), Duration::from_secs(60))
+                .tag(
+                    "idle",
+                    0..=1,
+                    AnimationDirection::Forward,
+                    AnimationRepeat::Loop,
+                )
+                .tag(
+                    "jump",
+                    1..=1,
+                    AnimationDirection::Forward,
+                    AnimationRepeat::Loop,
+                )
+                .build_atlas(&mut images, &mut layouts)
+                .unwrap()
+        });
+    let aseprite = app
+        .world_mut()
+        .resource_mut::<Assets<Aseprite>>()
+        .add(aseprite);
+    let entity = app
+        .world_mut()
+        .spawn((
+            AseAnimation {
+                aseprite,
+                animation: Animation::tag("idle"),
+            },
+            Sprite::default(),
+            Transform::from_xyz(10., 0., 0.),
+        ))
+        .id();
+    for _ in 0..2 {
+        app.update();
+    }
+
+    // above the sprite, without becoming its child
+    assert_eq!(
+        labels(&mut app),
+        [("idle 0".to_string(), Vec3::new(10., 2.5, 1.), false)]
+    );
+    assert!(app.world().get::<Children>(entity).is_none());
+
+    app.world_mut()
+        .entity_mut(entity)
+        .insert(Transform::from_xyz(0., 20., 0.))
+        .get_mut::<AseAnimation>()
+        .unwrap()
+        .animation
+        .play_loop("jump");
+    app.update();
+    assert_eq!(
+        labels(&mut app),
+        [("jump 1".to_string(), Vec3::new(0., 22.5, 1.), false)]
+    );
+
+    app.world_mut().entity_mut(entity).insert(AseDebug::Hide);
+    app.update();
+    assert!(labels(&mut app).is_empty());
+}
@@ -0,0 +1,58 @@
+#![cfg(feature = "asset_processing")]
+
+use bevy::{
+    asset::{
+        processor::{AssetProcessor, FileTransactionLogFactory},
+        AssetMode,
+    },
+    prelude::*,
+};
+use bevy_aseprite_ultra::testing::*;
+use std::path::Path;
+
+const ASSETS: [&str; 3] = ["player.aseprite", "ball.aseprite", "ghost_slices.aseprite"];
+
+/// runs the asset processor on the example assets into `out`
+fn process_assets(out: &Path) {
+    let mut app = headless_app(AssetPlugin {
+        mode: AssetMode::Processed,
+        processed_file_path: out.join("Default").to_string_lossy().into(),
+        ..default()
+    });
+
+    app.world()
+        .resource::<AssetProcessor>()
+        .data()
+        .set_log_factory(Box::new(FileTransactionLogFactory {
+            file_path: out.join("log"),
+        }))
+        .expect("log factory is set before processing starts");
+
+    finish_processing(&mut app);
+}
+
+#[test]
+fn processing_is_deterministic() {
+    let first = output_dir("deterministic_first");
+    let second = output_dir("deterministic_second");
+
+    process_assets(&first);
+    process_assets(&second);
+
+    for asset in ASSETS {
+        let source = std::fs::read(Path::new("assets").join(asset)).expect("example asset");
+        let a = std::fs::read(first.join("Default").join(asset)).expect("first run output");
+        let b = std::fs::read(second.join("Default").join(asset)).expect("second run output");
+        assert!(a != source, "{asset} was copied instead of processed");
+        assert!(a == b, "processed {asset} differs between runs");
+
+        // the meta holds the hashes of the output, which decide whether it is reprocessed
+        let meta = format!("{asset}.meta");
+        let a = std::fs::read(first.join("Default").join(&meta)).expect("first run meta");
+        let b = std::fs::read(second.join("Default").join(&meta)).expect("second run meta");
+        assert!(a == b, "processed {meta} differs between runs");
+    }
+
+    let _ = std::fs::remove_dir_all(first);
+    let _ = std::fs::remove_dir_all(second);
+}
@@ -0,0 +1,86 @@
+use bevy::{
+    asset::{LoadState, RenderAssetUsages},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    sprite::Anchor,
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+fn frame() -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: 8,
+            height: 8,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[255, 255, 255, 255],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    )
+}
+
+#[test]
+fn origin_and_pivot_slices_anchor_frames() {
+    let out = output_dir("frame_anchors");
+    let builder = || {
+        AsepriteBuilder::new()
+            .frame_image(frame(), Duration::from_millis(100))
+            .frame_image(frame(), Duration::from_millis(100))
+            .frame_image(frame(), Duration::from_millis(100))
+            .slice(
+                "hit",
+                Rect::new(0.0, 0.0, 4.0, 4.0),
+                Some(Vec2::new(2.0, 2.0)),
+            )
+    };
+    let with_origin = builder()
+        .slice_on(
+            "origin",
+            1,
+            Rect::new(2.0, 6.0, 6.0, 8.0),
+            Some(Vec2::new(2.0, 2.0)),
+        )
+        .write()
+        .unwrap();
+    std::fs::write(out.join("origin.aseprite"), with_origin).unwrap();
+    let without_origin = builder().write().unwrap();
+    std::fs::write(out.join("pivot.aseprite"), &without_origin).unwrap();
+    // the same file again, assets are cached by path regardless of the settings
+    std::fs::write(out.join("fixed.aseprite"), without_origin).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let handles = [
+        server.load::<Aseprite>("origin.aseprite"),
+        server.load::<Aseprite>("pivot.aseprite"),
+        server.load_with_settings::<Aseprite, _>(
+            "fixed.aseprite",
+            |settings: &mut AsepriteLoaderSettings| settings.origin = Some([0, 0]),
+        ),
+    ];
+    let states = load_states(&mut app, &handles);
+    assert!(
+        states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded)),
+        "{states:?}"
+    );
+
+    let aseprites = app.world().resource::<Assets<Aseprite>>();
+    let [origin, pivot, fixed] = handles.map(|handle| aseprites.get(&handle).unwrap());
+
+    let hit = Anchor(Vec2::new(-0.25, 0.25));
+
+    // frames before the first origin key follow the pivot slice
+    assert_eq!(origin.get_frame_anchor(0), Some(hit));
+    assert_eq!(origin.get_frame_anchor(1), Some(Anchor::BOTTOM_CENTER));
+    assert_eq!(origin.get_frame_anchor(2), Some(Anchor::BOTTOM_CENTER));
+
+    assert!((0..3).all(|frame| pivot.get_frame_anchor(frame) == Some(hit)));
+
+    assert!((0..3).all(|frame| fixed.get_frame_anchor(frame) == Some(Anchor::TOP_LEFT)));
+
+    let _ = std::fs::remove_dir_all(&out);
+}
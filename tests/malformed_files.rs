@@ -0,0 +1,150 @@
+use bevy::{
+    asset::{AssetLoadError, LoadState},
+    prelude::*,
+};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::path::Path;
+
+const ASSETS: [&str; 3] = ["player.aseprite", "ball.aseprite", "ghost_slices.aseprite"];
+
+/// deterministic xorshift, so failures are reproducible
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n.max(1) as u64) as usize
+    }
+}
+
+/// truncated, bit flipped and header patched variants of a valid file
+fn corruptions(name: &str, bytes: &[u8], rng: &mut Rng) -> Vec<(String, Vec<u8>)> {
+    let mut files = Vec::new();
+
+    for cut in [0, 1, 4, 16, 127, 128, 129, 200, 512] {
+        files.push((
+            format!("{name}_cut_{cut}"),
+            bytes[..cut.min(bytes.len())].to_vec(),
+        ));
+    }
+    for tenth in 1..10 {
+        let cut = bytes.len() * tenth / 10;
+        files.push((format!("{name}_cut_{cut}"), bytes[..cut].to_vec()));
+    }
+
+    for case in 0..32 {
+        let mut corrupt = bytes.to_vec();
+        for _ in 0..=rng.below(8) {
+            let at = rng.below(corrupt.len());
+            corrupt[at] ^= 1 << rng.below(8);
+        }
+        files.push((format!("{name}_flip_{case}"), corrupt));
+    }
+
+    // header: frame count at 6, width at 8, height at 10
+    for (field, at, value) in [
+        ("frames_zero", 6, [0, 0]),
+        ("frames_max", 6, [0xff, 0xff]),
+        ("width_zero", 8, [0, 0]),
+        ("width_max", 8, [0xff, 0xff]),
+        ("height_zero", 10, [0, 0]),
+        ("height_max", 10, [0xff, 0xff]),
+    ] {
+        let mut corrupt = bytes.to_vec();
+        corrupt[at..at + 2].copy_from_slice(&value);
+        files.push((format!("{name}_{field}"), corrupt));
+    }
+
+    let garbage = bytes.iter().map(|_| rng.next() as u8).collect();
+    files.push((format!("{name}_garbage"), garbage));
+
+    files
+}
+
+#[test]
+fn malformed_files_fail_without_panic() {
+    let out = output_dir("malformed");
+
+    let mut rng = Rng(0x5eed_a5e9_17e5);
+    let mut names = Vec::new();
+    for asset in ASSETS {
+        let bytes = std::fs::read(Path::new("assets").join(asset)).unwrap();
+        let stem = asset.trim_end_matches(".aseprite");
+        for (name, corrupt) in corruptions(stem, &bytes, &mut rng) {
+            let file = format!("{name}.aseprite");
+            std::fs::write(out.join(&file), corrupt).unwrap();
+            names.push(file);
+        }
+    }
+
+    let mut app = asset_app(&out);
+    let handles = names
+        .iter()
+        .map(|name| {
+            app.world()
+                .resource::<AssetServer>()
+                .load::<Aseprite>(name.clone())
+        })
+        .collect::<Vec<_>>();
+
+    // bevy catches loader panics and reports them as a failed load
+    let states = load_states(&mut app, &handles);
+    let panicked = names
+        .iter()
+        .zip(&states)
+        .filter(|(_, state)| {
+            matches!(state, LoadState::Failed(err) if matches!(**err, AssetLoadError::AssetLoaderPanic { .. }))
+        })
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+    let pending = names
+        .iter()
+        .zip(&states)
+        .filter(|(_, state)| !matches!(state, LoadState::Loaded | LoadState::Failed(_)))
+        .map(|(name, _)| name.clone())
+        .collect::<Vec<_>>();
+
+    // whatever did load must also animate
+    for handle in handles.iter() {
+        app.world_mut().spawn((
+            AseAnimation {
+                aseprite: handle.clone(),
+                animation: Animation::default(),
+            },
+            Sprite::default(),
+        ));
+    }
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let _ = std::fs::remove_dir_all(&out);
+    assert!(panicked.is_empty(), "loader panicked: {panicked:?}");
+    assert!(pending.is_empty(), "loader did not finish: {pending:?}");
+}
+
+#[test]
+fn empty_aseprite_animates_without_panic() {
+    let mut app = headless_app(AssetPlugin::default());
+    let aseprite = app
+        .world_mut()
+        .resource_mut::<Assets<Aseprite>>()
+        .add(Aseprite::default());
+    app.world_mut().spawn((
+        AseAnimation {
+            aseprite,
+            animation: Animation::default().with_direction(AnimationDirection::PingPong),
+        },
+        Sprite::default(),
+    ));
+
+    for _ in 0..10 {
+        app.update();
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+const SHEET: &str = r#"{
+  "frames": [
+    {
+      "filename": "sheet 0.aseprite",
+      "frame": { "x": 0, "y": 0, "w": 8, "h": 8 },
+      "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 },
+      "sourceSize": { "w": 8, "h": 8 },
+      "duration": 100
+    },
+    {
+      "filename": "sheet 1.aseprite",
+      "frame": { "x": 8, "y": 0, "w": 8, "h": 8 },
+      "spriteSourceSize": { "x": 0, "y": 0, "w": 8, "h": 8 },
+      "sourceSize": { "w": 8, "h": 8 },
+      "duration": 250
+    }
+  ],
+  "meta": {
+    "image": "sheet.png",
+    "size": { "w": 16, "h": 8 },
+    "frameTags": [{ "name": "blink", "from": 0, "to": 1, "direction": "pingpong" }]
+  }
+}"#;
+
+#[test]
+fn only_aseprite_json_is_claimed() {
+    let out = output_dir("sprite_sheet");
+    // any 16x8 png will do
+    std::fs::copy("tests/blend/normal.png", out.join("sheet.png")).unwrap();
+    std::fs::write(out.join("sheet.aseprite.json"), SHEET).unwrap();
+    std::fs::write(out.join("sheet.json"), SHEET).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let sheet = server.load::<Aseprite>("sheet.aseprite.json");
+    let other = server.load_untyped("sheet.json");
+    let states = load_states(&mut app, std::slice::from_ref(&sheet));
+    assert!(matches!(states[0], LoadState::Loaded), "{states:?}");
+    let states = load_states(&mut app, &[other]);
+    assert!(matches!(states[0], LoadState::Failed(_)), "{states:?}");
+
+    let aseprites = app.world().resource::<Assets<Aseprite>>();
+    let sheet = aseprites.get(&sheet).unwrap();
+    assert_eq!(sheet.frame_durations, [100, 250].map(Duration::from_millis));
+    assert_eq!(sheet.tags.get("blink").unwrap().range, 0..=1);
+
+    let _ = std::fs::remove_dir_all(&out);
+}
+
+#[test]
+fn hostile_sheets_are_clamped_or_rejected() {
+    let out = output_dir("hostile_sheet");
+    std::fs::copy("tests/blend/normal.png", out.join("sheet.png")).unwrap();
+    let tags = SHEET.replace(
+        r#"{ "name": "blink", "from": 0, "to": 1, "direction": "pingpong" }"#,
+        r#"{ "name": "inverted", "from": 1, "to": 0, "direction": "forward" },
+        { "name": "past", "from": 1, "to": 9, "direction": "reverse" }"#,
+    );
+    std::fs::write(out.join("tags.aseprite.json"), tags).unwrap();
+    let huge = SHEET.replace(
+        r#""sourceSize": { "w": 8, "h": 8 }"#,
+        r#""sourceSize": { "w": 100000, "h": 100000 }"#,
+    );
+    std::fs::write(out.join("huge.aseprite.json"), huge).unwrap();
+    let empty =
+        r#"{ "frames": [], "meta": { "image": "sheet.png", "size": { "w": 16, "h": 8 } } }"#;
+    std::fs::write(out.join("empty.aseprite.json"), empty).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let handles = ["tags", "huge", "empty"]
+        .map(|name| server.load::<Aseprite>(format!("{name}.aseprite.json")));
+    let states = load_states(&mut app, &handles);
+    assert!(matches!(states[0], LoadState::Loaded), "{states:?}");
+    assert!(matches!(states[1], LoadState::Failed(_)), "{states:?}");
+    assert!(matches!(states[2], LoadState::Failed(_)), "{states:?}");
+
+    let aseprite = app
+        .world()
+        .resource::<Assets<Aseprite>>()
+        .get(&handles[0])
+        .unwrap();
+    assert_eq!(aseprite.tags.get("inverted").unwrap().range, 0..=0);
+    assert_eq!(aseprite.tags.get("past").unwrap().range, 1..=1);
+
+    for tag in ["inverted", "past"] {
+        app.world_mut().spawn((
+            AseAnimation {
+                aseprite: handles[0].clone(),
+                animation: Animation::tag(tag),
+            },
+            Sprite::default(),
+        ));
+    }
+    for _ in 0..10 {
+        app.update();
+    }
+
+    let _ = std::fs::remove_dir_all(&out);
+}
@@ -0,0 +1,54 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+#[test]
+fn sub_images_are_opt_in() {
+    let out = output_dir("sub_images");
+    let bytes = AsepriteBuilder::new()
+        .frames(2, Duration::from_millis(100))
+        .slice("hit", Rect::new(0.0, 0.0, 1.0, 1.0), None)
+        .write()
+        .unwrap();
+    std::fs::write(out.join("default.aseprite"), &bytes).unwrap();
+    std::fs::write(out.join("enabled.aseprite"), bytes).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let settings = |settings: &mut AsepriteLoaderSettings| {
+        settings.sub_images = SubImages {
+            slices: true,
+            frames: true,
+        };
+    };
+    let files = [
+        server.load::<Aseprite>("default.aseprite"),
+        server.load_with_settings::<Aseprite, _>("enabled.aseprite", settings),
+    ];
+    let images = [
+        server.load::<Image>("default.aseprite#slice/hit"),
+        server.load_with_settings::<Image, _>("enabled.aseprite#slice/hit", settings),
+        server.load_with_settings::<Image, _>("enabled.aseprite#frame/1", settings),
+    ];
+    let states = load_states(&mut app, &files);
+    assert!(
+        states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded)),
+        "{states:?}"
+    );
+    let states = load_states(&mut app, &images[1..]);
+    assert!(
+        states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded)),
+        "{states:?}"
+    );
+
+    // the file is loaded, but without a slice image
+    let assets = app.world().resource::<Assets<Image>>();
+    assert!(assets.get(&images[0]).is_none());
+    assert_eq!(assets.get(&images[1]).unwrap().size(), UVec2::ONE);
+
+    let _ = std::fs::remove_dir_all(&out);
+}
@@ -0,0 +1,74 @@
+use bevy::{asset::LoadState, prelude::*};
+use bevy_aseprite_ultra::{prelude::*, testing::*};
+use std::time::Duration;
+
+#[test]
+fn tag_assets_share_the_file_atlas() {
+    let out = output_dir("tag_assets");
+    let bytes = AsepriteBuilder::new()
+        .frames(6, Duration::from_millis(100))
+        .frame(Duration::from_millis(300))
+        .tag(
+            "idle",
+            0..=1,
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        )
+        .tag(
+            "walk",
+            4..=6,
+            AnimationDirection::PingPong,
+            AnimationRepeat::Count(2),
+        )
+        .tag(
+            "jump",
+            2..=3,
+            AnimationDirection::Forward,
+            AnimationRepeat::Loop,
+        )
+        .slice("hit", Rect::new(0.0, 0.0, 4.0, 4.0), None)
+        .write()
+        .unwrap();
+    std::fs::write(out.join("player.aseprite"), bytes).unwrap();
+
+    let mut app = asset_app(&out);
+    let server = app.world().resource::<AssetServer>().clone();
+    let settings = |settings: &mut AsepriteLoaderSettings| {
+        settings.tags = Some(vec!["idle".into(), "walk".into()]);
+        settings.tag_assets = true;
+    };
+    let file = server.load_with_settings::<Aseprite, _>("player.aseprite", settings);
+    let walk = server.load_with_settings::<Aseprite, _>("player.aseprite#tag:walk", settings);
+    let jump = server.load_with_settings::<Aseprite, _>("player.aseprite#tag:jump", settings);
+    let states = load_states(&mut app, &[file.clone(), walk.clone()]);
+    assert!(
+        states
+            .iter()
+            .all(|state| matches!(state, LoadState::Loaded)),
+        "{states:?}"
+    );
+
+    let aseprites = app.world().resource::<Assets<Aseprite>>();
+    let file = aseprites.get(&file).unwrap();
+    let walk = aseprites.get(&walk).unwrap();
+
+    // only the frames of the loaded tags are in the one atlas
+    assert_eq!(file.frame_durations.len(), 5);
+    assert_eq!(walk.atlas_image, file.atlas_image);
+    assert_eq!(walk.atlas_layout, file.atlas_layout);
+    assert!(aseprites.get(&jump).is_none());
+
+    assert_eq!(
+        walk.frame_durations,
+        [100, 100, 300].map(Duration::from_millis)
+    );
+    let tag = walk.tags.get("walk").unwrap();
+    assert_eq!(tag.range, 0..=2);
+    assert_eq!(tag.repeat, 2);
+    for frame in 0..3 {
+        assert_eq!(walk.get_atlas_index(frame), file.get_atlas_index(frame + 2));
+    }
+    assert_eq!(walk.slices.get("hit").unwrap().keys[0].frame, 0);
+
+    let _ = std::fs::remove_dir_all(&out);
+}